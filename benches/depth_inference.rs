@@ -0,0 +1,194 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use depth_browser::api::circuit_breaker::CircuitBreaker;
+use depth_browser::api::colormap::Colormap;
+use depth_browser::api::depth::{DepthInferenceQueue, DepthMap, ModelHealth, SharedDepthModel};
+use depth_browser::api::post_process::DepthPostProcessor;
+use depth_browser::config::{DepthConfig, DepthDevice, PostProcessConfig};
+use image::codecs::jpeg::JpegEncoder;
+use image::{ImageBuffer, Rgb, RgbImage};
+use std::hint::black_box;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 480;
+
+/// Gradient frame so the encoder has real work to do
+fn synthetic_frame() -> RgbImage {
+    ImageBuffer::from_fn(WIDTH, HEIGHT, |x, y| {
+        Rgb([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8])
+    })
+}
+
+fn encode_jpeg(frame: &RgbImage, quality: u8) -> Vec<u8> {
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, quality)
+        .encode_image(frame)
+        .expect("JPEG encode");
+    out
+}
+
+/// JPEG encoding cost at the qualities clients typically send
+fn bench_jpeg_encode(c: &mut Criterion) {
+    let frame = synthetic_frame();
+    let mut group = c.benchmark_group("jpeg_encode_640x480");
+    group.throughput(Throughput::Bytes((WIDTH * HEIGHT * 3) as u64));
+    for quality in [50u8, 70, 85, 95] {
+        group.bench_with_input(BenchmarkId::from_parameter(quality), &quality, |b, &q| {
+            b.iter(|| encode_jpeg(black_box(&frame), q))
+        });
+    }
+    group.finish();
+}
+
+/// Round trip through the inference queue without a loaded model:
+/// measures admission, the worker hand-off, the model lock and the reply
+fn bench_inference_overhead(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("tokio runtime");
+    let model: SharedDepthModel = Arc::new(Mutex::new(None));
+    let config = DepthConfig {
+        enabled: true,
+        warm_standby: false,
+        queue_depth: 4,
+        // Every frame is identical, a cache would skip the worker
+        cache_size: 0,
+        device: DepthDevice::Auto,
+        max_concurrent: 4,
+        queue_timeout_ms: 1000,
+        post_process: PostProcessConfig::default(),
+    };
+    // The worker is a spawn_blocking task, so it needs the runtime context
+    let queue = runtime.block_on(async {
+        DepthInferenceQueue::spawn(
+            model,
+            Arc::new(CircuitBreaker::default()),
+            Arc::new(ModelHealth::default()),
+            &config,
+        )
+    });
+    let jpeg = encode_jpeg(&synthetic_frame(), 85);
+
+    c.bench_function("depth_queue_overhead", |b| {
+        b.to_async(&runtime).iter(|| {
+            let jpeg = jpeg.clone();
+            let queue = &queue;
+            async move {
+                let result = queue.infer(black_box(jpeg)).await;
+                assert!(result.is_err());
+            }
+        })
+    });
+}
+
+/// Noisy mid-range depth map, roughly what the model returns for a dim scene
+fn synthetic_depth_map() -> DepthMap {
+    let pixels = (0..WIDTH * HEIGHT)
+        .map(|i| {
+            let (x, y) = (i % WIDTH, i / WIDTH);
+            let noise = i.wrapping_mul(2_654_435_761) >> 28;
+            (64 + (x + y) * 96 / (WIDTH + HEIGHT) + noise) as u8
+        })
+        .collect();
+    DepthMap {
+        width: WIDTH,
+        height: HEIGHT,
+        pixels,
+    }
+}
+
+/// Post-processing pipeline variants on a 640x480 u8 map
+fn bench_post_process(c: &mut Criterion) {
+    let map = synthetic_depth_map();
+    let variants = [
+        ("none", PostProcessConfig::default()),
+        (
+            "contrast_stretch",
+            PostProcessConfig {
+                contrast_stretch: true,
+                median_filter_radius: None,
+                invert: false,
+            },
+        ),
+        (
+            "median_r1",
+            PostProcessConfig {
+                contrast_stretch: false,
+                median_filter_radius: Some(1),
+                invert: false,
+            },
+        ),
+        (
+            "median_r2",
+            PostProcessConfig {
+                contrast_stretch: false,
+                median_filter_radius: Some(2),
+                invert: false,
+            },
+        ),
+        (
+            "full",
+            PostProcessConfig {
+                contrast_stretch: true,
+                median_filter_radius: Some(1),
+                invert: true,
+            },
+        ),
+    ];
+
+    let mut group = c.benchmark_group("post_process_640x480");
+    group.throughput(Throughput::Bytes((WIDTH * HEIGHT) as u64));
+    for (name, config) in variants {
+        let processor = DepthPostProcessor::new(config);
+        group.bench_with_input(BenchmarkId::from_parameter(name), &processor, |b, p| {
+            b.iter(|| {
+                let mut map = DepthMap {
+                    width: map.width,
+                    height: map.height,
+                    pixels: map.pixels.clone(),
+                };
+                p.apply(black_box(&mut map));
+                map
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Colourising a 640x480 map for /api/depth/preview
+fn bench_colormap(c: &mut Criterion) {
+    let map = synthetic_depth_map();
+    let mut group = c.benchmark_group("colormap_640x480");
+    group.throughput(Throughput::Bytes((WIDTH * HEIGHT) as u64));
+    for (name, colormap) in [("viridis", Colormap::Viridis), ("turbo", Colormap::Turbo)] {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &colormap, |b, cm| {
+            b.iter(|| cm.apply(black_box(&map)))
+        });
+    }
+    group.finish();
+}
+
+/// zstd cost per /ws/depth result at the levels clients are likely to pick
+fn bench_zstd(c: &mut Criterion) {
+    let payload = synthetic_depth_map().to_bytes();
+    let mut group = c.benchmark_group("zstd_640x480");
+    group.throughput(Throughput::Bytes(payload.len() as u64));
+    for level in [1, 3, 9] {
+        group.bench_with_input(BenchmarkId::from_parameter(level), &level, |b, &level| {
+            b.iter(|| zstd::encode_all(black_box(payload.as_slice()), level).expect("zstd"))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_jpeg_encode,
+    bench_inference_overhead,
+    bench_post_process,
+    bench_colormap,
+    bench_zstd
+);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+use vergen_gitcl::{BuildBuilder, Emitter, GitclBuilder};
+
+fn main() -> anyhow::Result<()> {
+    // Falls back to defaults (with a cargo warning) outside a git checkout
+    let build = BuildBuilder::default().build_date(true).build()?;
+    let git = GitclBuilder::default().sha(true).build()?;
+    Emitter::default()
+        .add_instructions(&build)?
+        .add_instructions(&git)?
+        .emit()
+}
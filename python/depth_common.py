@@ -0,0 +1,21 @@
+"""
+Output helpers shared by the depth estimator modules.
+Wire format is documented in PYTHON_API.md.
+"""
+
+import numpy as np
+
+
+def normalize(depth: np.ndarray, scale: float) -> np.ndarray:
+    """Min-max normalize depth to 0..scale."""
+    depth_min, depth_max = depth.min(), depth.max()
+    if depth_max - depth_min > 0:
+        return (depth - depth_min) / (depth_max - depth_min) * scale
+    return np.zeros_like(depth)
+
+
+def pack(depth: np.ndarray, dtype: str) -> bytes:
+    """Width, height as 2-byte big-endian, then samples as little-endian dtype."""
+    h, w = depth.shape
+    header = w.to_bytes(2, 'big') + h.to_bytes(2, 'big')
+    return header + depth.astype(dtype).tobytes()
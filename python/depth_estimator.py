@@ -9,18 +9,24 @@ import io
 import numpy as np
 from PIL import Image
 
-# Lazy load model components
-_model = None
-_image_processor = None
-_device = None
-_backend = None
-_torch_dtype = None
+from depth_common import normalize, pack
 
-
-def _detect_best_device():
-    """Detect the best available compute device."""
+def _detect_best_device(device: str = "auto"):
+    """Resolve a device ("auto", "cpu", "cuda:N", "mps") to a torch device and label."""
     import torch
 
+    if device == "cpu":
+        return "cpu", "CPU"
+    if device.startswith("cuda"):
+        if not torch.cuda.is_available():
+            raise RuntimeError("CUDA requested but torch.cuda is not available")
+        index = int(device.partition(":")[2] or 0)
+        return f"cuda:{index}", f"CUDA ({torch.cuda.get_device_name(index)})"
+    if device == "mps":
+        if not (hasattr(torch.backends, "mps") and torch.backends.mps.is_available()):
+            raise RuntimeError("MPS requested but torch.backends.mps is not available")
+        return "mps", "MPS (Apple Silicon)"
+
     # Check CUDA (includes ROCm which presents as CUDA)
     if torch.cuda.is_available():
         device_name = torch.cuda.get_device_name(0)
@@ -55,31 +61,26 @@ def _get_cache_dir():
     return os.path.abspath(cache_dir)
 
 
-def _ensure_model():
-    """Lazy load the model on first use."""
-    global _model, _image_processor, _device, _backend, _torch_dtype
-
-    if _model is not None:
-        return
-
+def _load_model(device: str = "auto"):
+    """Load the model on `device`. Returns (model, image_processor, torch_device, backend, torch_dtype)."""
     import os
     import torch
     from transformers import AutoImageProcessor, AutoModelForDepthEstimation
 
-    _device, _backend = _detect_best_device()
-    print(f"[DEPTH] Using device: {_backend}", flush=True)
+    torch_device, backend = _detect_best_device(device)
+    print(f"[DEPTH] Using device: {backend}", flush=True)
 
     # Determine dtype based on device
-    is_directml = isinstance(_device, torch.device) or (
-        hasattr(_device, "type") and "privateuseone" in str(_device).lower()
+    is_directml = isinstance(torch_device, torch.device) or (
+        hasattr(torch_device, "type") and "privateuseone" in str(torch_device).lower()
     )
 
     if is_directml:
-        _torch_dtype = torch.float32
-    elif _device in ("cuda", "mps"):
-        _torch_dtype = torch.float16
+        torch_dtype = torch.float32
+    elif str(torch_device).startswith(("cuda", "mps")):
+        torch_dtype = torch.float16
     else:
-        _torch_dtype = torch.float32
+        torch_dtype = torch.float32
 
     model_id = "depth-anything/Depth-Anything-V2-Small-hf"
     cache_dir = _get_cache_dir()
@@ -88,45 +89,66 @@ def _ensure_model():
     # Try offline first (cached), fall back to download
     try:
         print(f"[DEPTH] Loading from cache: {cache_dir}", flush=True)
-        _image_processor = AutoImageProcessor.from_pretrained(
+        image_processor = AutoImageProcessor.from_pretrained(
             model_id, use_fast=True, cache_dir=cache_dir, local_files_only=True
         )
-        _model = AutoModelForDepthEstimation.from_pretrained(
-            model_id, torch_dtype=_torch_dtype, cache_dir=cache_dir, local_files_only=True
+        model = AutoModelForDepthEstimation.from_pretrained(
+            model_id, torch_dtype=torch_dtype, cache_dir=cache_dir, local_files_only=True
         )
         print("[DEPTH] Loaded from local cache", flush=True)
     except Exception:
         print("[DEPTH] Cache miss, downloading model...", flush=True)
-        _image_processor = AutoImageProcessor.from_pretrained(
+        image_processor = AutoImageProcessor.from_pretrained(
             model_id, use_fast=True, cache_dir=cache_dir
         )
-        _model = AutoModelForDepthEstimation.from_pretrained(
-            model_id, torch_dtype=_torch_dtype, cache_dir=cache_dir
+        model = AutoModelForDepthEstimation.from_pretrained(
+            model_id, torch_dtype=torch_dtype, cache_dir=cache_dir
         )
         print("[DEPTH] Model downloaded and cached", flush=True)
 
     # Move to device
-    if _device != "cpu":
-        _model = _model.to(_device)
-        print(f"[DEPTH] Model moved to {_device}", flush=True)
+    if torch_device != "cpu":
+        model = model.to(torch_device)
+        print(f"[DEPTH] Model moved to {torch_device}", flush=True)
 
     # Compile for CUDA only (not DirectML/MPS)
-    if hasattr(torch, "compile") and _device == "cuda":
+    if hasattr(torch, "compile") and torch_device == "cuda":
         try:
-            _model = torch.compile(_model, mode="reduce-overhead")
+            model = torch.compile(model, mode="reduce-overhead")
             print("[DEPTH] Model compiled with torch.compile", flush=True)
         except Exception as e:
             print(f"[DEPTH] torch.compile skipped: {e}", flush=True)
 
-    _model.eval()
+    model.eval()
     print("[DEPTH] Model loaded successfully", flush=True)
+    return model, image_processor, torch_device, backend, torch_dtype
+
+
+def reset():
+    """Release memory held by dropped models before a reload."""
+    import gc
+    import torch
+
+    gc.collect()
+    if torch.cuda.is_available():
+        torch.cuda.empty_cache()
+
+
+def _decode_jpeg(jpeg_bytes: bytes) -> Image.Image:
+    """Decode JPEG bytes to PIL Image. Raises ValueError for undecodable input."""
+    try:
+        return Image.open(io.BytesIO(jpeg_bytes)).convert("RGB")
+    except OSError as e:
+        # PIL's UnidentifiedImageError is an OSError
+        raise ValueError(f"Invalid image: {e}") from e
 
 
 class DepthEstimator:
-    """Depth estimation wrapper called from Rust via PyO3."""
+    """Depth estimation wrapper called from Rust via PyO3. Each instance owns its model."""
 
-    def __init__(self):
-        _ensure_model()
+    def __init__(self, device: str = "auto"):
+        (self._model, self._image_processor, self._torch_device,
+         self.device, self._torch_dtype) = _load_model(device)
 
     def estimate(self, jpeg_bytes: bytes) -> bytes:
         """
@@ -143,24 +165,24 @@ class DepthEstimator:
 
         t0 = time.perf_counter()
 
-        image = Image.open(io.BytesIO(jpeg_bytes)).convert("RGB")
+        image = _decode_jpeg(jpeg_bytes)
         orig_size = image.size
         t1 = time.perf_counter()
 
         # Preprocess
-        inputs = _image_processor(images=image, return_tensors="pt")
+        inputs = self._image_processor(images=image, return_tensors="pt")
         t2 = time.perf_counter()
 
         # Move inputs to device
-        if _device != "cpu":
-            inputs = {k: v.to(_device) for k, v in inputs.items()}
-        if _torch_dtype == torch.float16:
+        if self._torch_device != "cpu":
+            inputs = {k: v.to(self._torch_device) for k, v in inputs.items()}
+        if self._torch_dtype == torch.float16:
             inputs = {k: v.half() if v.dtype == torch.float32 else v for k, v in inputs.items()}
         t3 = time.perf_counter()
 
         # Inference
         with torch.no_grad():
-            outputs = _model(**inputs)
+            outputs = self._model(**inputs)
             predicted_depth = outputs.predicted_depth.squeeze()
         t4 = time.perf_counter()
 
@@ -171,10 +193,7 @@ class DepthEstimator:
         depth = predicted_depth.cpu().numpy()
         t6 = time.perf_counter()
 
-        depth_min, depth_max = depth.min(), depth.max()
-        if depth_max - depth_min > 0:
-            depth = (depth - depth_min) / (depth_max - depth_min) * 255
-        depth = depth.astype(np.uint8)
+        depth = normalize(depth, 255)
         t7 = time.perf_counter()
 
         # Log timing every ~100 frames
@@ -190,7 +209,26 @@ class DepthEstimator:
                   f"interp={1000*(t5-t4):.1f}ms cpu={1000*(t6-t5):.1f}ms norm={1000*(t7-t6):.1f}ms "
                   f"total={1000*(t7-t0):.1f}ms size={w}x{h}", flush=True)
 
-        # Prepend dimensions (width, height as 2-byte big-endian)
-        h, w = depth.shape
-        header = w.to_bytes(2, 'big') + h.to_bytes(2, 'big')
-        return header + depth.tobytes()
+        return pack(depth, 'u1')
+
+    def _predict(self, jpeg_bytes: bytes) -> np.ndarray:
+        """Raw relative depth (float32, H x W)."""
+        import torch
+
+        image = _decode_jpeg(jpeg_bytes)
+        inputs = self._image_processor(images=image, return_tensors="pt")
+        if self._torch_device != "cpu":
+            inputs = {k: v.to(self._torch_device) for k, v in inputs.items()}
+        if self._torch_dtype == torch.float16:
+            inputs = {k: v.half() if v.dtype == torch.float32 else v for k, v in inputs.items()}
+        with torch.no_grad():
+            predicted_depth = self._model(**inputs).predicted_depth.squeeze()
+        return predicted_depth.float().cpu().numpy()
+
+    def estimate_u16(self, jpeg_bytes: bytes) -> bytes:
+        """Depth normalized to 0-65535 as uint16."""
+        return pack(normalize(self._predict(jpeg_bytes), 65535), '<u2')
+
+    def estimate_f32(self, jpeg_bytes: bytes) -> bytes:
+        """Unnormalized model output as float32."""
+        return pack(self._predict(jpeg_bytes), '<f4')
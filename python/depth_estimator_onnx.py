@@ -10,9 +10,7 @@ import numpy as np
 from PIL import Image
 from pathlib import Path
 
-_session = None
-_backend = None
-_input_name = None
+from depth_common import normalize, pack
 
 # Try TurboJPEG for faster decoding (optional)
 try:
@@ -42,11 +40,23 @@ def _get_onnx_model_path():
     return None
 
 
-def _detect_best_provider():
-    """Detect best ONNX Runtime execution provider."""
+def _detect_best_provider(device: str = "auto"):
+    """Pick ONNX Runtime execution providers for a device ("auto", "cpu", "cuda:N", "mps")."""
     import onnxruntime as ort
     providers = ort.get_available_providers()
 
+    if device == "cpu":
+        return ["CPUExecutionProvider"], "CPU"
+    if device.startswith("cuda"):
+        if "CUDAExecutionProvider" not in providers:
+            raise RuntimeError("CUDA requested but CUDAExecutionProvider is not available")
+        device_id = int(device.partition(":")[2] or 0)
+        return [("CUDAExecutionProvider", {"device_id": device_id}), "CPUExecutionProvider"], f"CUDA:{device_id}"
+    if device == "mps":
+        if "CoreMLExecutionProvider" not in providers:
+            raise RuntimeError("MPS requested but CoreMLExecutionProvider is not available")
+        return ["CoreMLExecutionProvider", "CPUExecutionProvider"], "CoreML"
+
     if "DmlExecutionProvider" in providers:
         return ["DmlExecutionProvider", "CPUExecutionProvider"], "DirectML"
     if "CUDAExecutionProvider" in providers:
@@ -54,21 +64,16 @@ def _detect_best_provider():
     return ["CPUExecutionProvider"], "CPU"
 
 
-def _ensure_session():
-    """Initialize ONNX Runtime session with warmup."""
-    global _session, _backend, _input_name
-
-    if _session is not None:
-        return
-
+def _load_session(device: str = "auto"):
+    """Create an ONNX Runtime session on `device` with warmup. Returns (session, backend, input_name)."""
     import onnxruntime as ort
 
     model_path = _get_onnx_model_path()
     if not model_path:
         raise RuntimeError(f"ONNX model not found in {ONNX_MODEL_DIR}\nRun: just src::download-models")
 
-    providers, _backend = _detect_best_provider()
-    print(f"[DEPTH-ONNX] Using: {_backend}", flush=True)
+    providers, backend = _detect_best_provider(device)
+    print(f"[DEPTH-ONNX] Using: {backend}", flush=True)
     print(f"[DEPTH-ONNX] Model: {model_path}", flush=True)
     if _use_turbojpeg:
         print("[DEPTH-ONNX] TurboJPEG: enabled", flush=True)
@@ -76,14 +81,14 @@ def _ensure_session():
     sess_options = ort.SessionOptions()
     sess_options.graph_optimization_level = ort.GraphOptimizationLevel.ORT_ENABLE_ALL
     # DirectML requires sequential execution and no memory pattern
-    if _backend == "DirectML":
+    if backend == "DirectML":
         sess_options.execution_mode = ort.ExecutionMode.ORT_SEQUENTIAL
         sess_options.enable_mem_pattern = False
 
-    _session = ort.InferenceSession(model_path, sess_options=sess_options, providers=providers)
-    _input_name = _session.get_inputs()[0].name
+    session = ort.InferenceSession(model_path, sess_options=sess_options, providers=providers)
+    input_name = session.get_inputs()[0].name
 
-    print(f"[DEPTH-ONNX] Input: {_input_name} {_session.get_inputs()[0].shape}", flush=True)
+    print(f"[DEPTH-ONNX] Input: {input_name} {session.get_inputs()[0].shape}", flush=True)
 
     # Warmup: run 3 dummy inferences to initialize GPU
     max_size = int(os.environ.get("NEXT_PUBLIC_DEPTH_INFERENCE_BASE", "280"))
@@ -92,18 +97,29 @@ def _ensure_session():
     dummy = np.random.randn(1, 3, dummy_h, dummy_w).astype(np.float32)
     print("[DEPTH-ONNX] Warming up...", flush=True)
     for _ in range(3):
-        _session.run(None, {_input_name: dummy})
+        session.run(None, {input_name: dummy})
     print("[DEPTH-ONNX] Session ready", flush=True)
+    return session, backend, input_name
+
+
+def reset():
+    """Release memory held by dropped sessions before a reload."""
+    import gc
+    gc.collect()
 
 
 def _decode_jpeg(jpeg_bytes: bytes) -> Image.Image:
-    """Decode JPEG bytes to PIL Image."""
-    if _use_turbojpeg:
-        # TurboJPEG returns BGR numpy array
-        bgr = _tjpeg.decode(jpeg_bytes)
-        rgb = bgr[:, :, ::-1]  # BGR -> RGB
-        return Image.fromarray(rgb)
-    return Image.open(io.BytesIO(jpeg_bytes)).convert("RGB")
+    """Decode JPEG bytes to PIL Image. Raises ValueError for undecodable input."""
+    try:
+        if _use_turbojpeg:
+            # TurboJPEG returns BGR numpy array
+            bgr = _tjpeg.decode(jpeg_bytes)
+            rgb = bgr[:, :, ::-1]  # BGR -> RGB
+            return Image.fromarray(rgb)
+        return Image.open(io.BytesIO(jpeg_bytes)).convert("RGB")
+    except OSError as e:
+        # PIL's UnidentifiedImageError and TurboJPEG failures are both OSError
+        raise ValueError(f"Invalid image: {e}") from e
 
 
 def _preprocess(image: Image.Image, max_size: int) -> tuple[np.ndarray, tuple[int, int]]:
@@ -125,10 +141,10 @@ def _preprocess(image: Image.Image, max_size: int) -> tuple[np.ndarray, tuple[in
 
 
 class DepthEstimatorONNX:
-    """ONNX Runtime depth estimator."""
+    """ONNX Runtime depth estimator. Each instance owns its session, so a standby is a real second model."""
 
-    def __init__(self):
-        _ensure_session()
+    def __init__(self, device: str = "auto"):
+        self._session, self.device, self._input_name = _load_session(device)
         self._frame_count = 0
 
     def estimate(self, jpeg_bytes: bytes) -> bytes:
@@ -142,15 +158,11 @@ class DepthEstimatorONNX:
         input_tensor, _ = _preprocess(image, max_size=max_size)
         t2 = time.perf_counter()
 
-        outputs = _session.run(None, {_input_name: input_tensor})
+        outputs = self._session.run(None, {self._input_name: input_tensor})
         depth = outputs[0].squeeze()
         t3 = time.perf_counter()
 
-        # Normalize to 0-255
-        depth_min, depth_max = depth.min(), depth.max()
-        if depth_max - depth_min > 0:
-            depth = (depth - depth_min) / (depth_max - depth_min) * 255
-        depth = depth.astype(np.uint8)
+        depth = normalize(depth, 255)
         t4 = time.perf_counter()
 
         self._frame_count += 1
@@ -160,10 +172,23 @@ class DepthEstimatorONNX:
                   f"infer={1000*(t3-t2):.1f}ms norm={1000*(t4-t3):.1f}ms "
                   f"total={1000*(t4-t0):.1f}ms size={w}x{h}", flush=True)
 
-        # Return with header (width, height as 2-byte big-endian)
-        h, w = depth.shape
-        header = w.to_bytes(2, 'big') + h.to_bytes(2, 'big')
-        return header + depth.tobytes()
+        return pack(depth, 'u1')
+
+    def _predict(self, jpeg_bytes: bytes) -> np.ndarray:
+        """Raw relative depth (float32, H x W)."""
+        image = _decode_jpeg(jpeg_bytes)
+        max_size = int(os.environ.get("NEXT_PUBLIC_DEPTH_INFERENCE_BASE", "280"))
+        input_tensor, _ = _preprocess(image, max_size=max_size)
+        outputs = self._session.run(None, {self._input_name: input_tensor})
+        return outputs[0].squeeze().astype(np.float32)
+
+    def estimate_u16(self, jpeg_bytes: bytes) -> bytes:
+        """Depth normalized to 0-65535 as uint16."""
+        return pack(normalize(self._predict(jpeg_bytes), 65535), '<u2')
+
+    def estimate_f32(self, jpeg_bytes: bytes) -> bytes:
+        """Unnormalized model output as float32."""
+        return pack(self._predict(jpeg_bytes), '<f4')
 
 
 # Alias for compatibility
@@ -0,0 +1,180 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU8, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::depth::is_model_fault;
+
+/// Consecutive failures that open the circuit
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+/// How long the circuit stays open before a probe is let through
+pub const DEFAULT_RESET_AFTER: Duration = Duration::from_secs(30);
+
+const CLOSED: u8 = 0;
+const OPEN: u8 = 1;
+const HALF_OPEN: u8 = 2;
+
+/// Breaker state as seen by callers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    /// Failing fast since the given instant
+    Open(Instant),
+    /// One probe call in flight
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open(_) => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// Call rejected because the circuit is open
+#[derive(Debug)]
+pub struct CircuitOpen;
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Depth model circuit open after repeated failures")
+    }
+}
+
+impl std::error::Error for CircuitOpen {}
+
+/// Lock-free breaker: Closed -> Open after N failures -> HalfOpen probe -> Closed/Open
+pub struct CircuitBreaker {
+    state: AtomicU8,
+    failures: AtomicU32,
+    /// Millis since `epoch` when the circuit last opened
+    opened_at_ms: AtomicU64,
+    epoch: Instant,
+    failure_threshold: u32,
+    reset_after: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(DEFAULT_FAILURE_THRESHOLD, DEFAULT_RESET_AFTER)
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, reset_after: Duration) -> Self {
+        Self {
+            state: AtomicU8::new(CLOSED),
+            failures: AtomicU32::new(0),
+            opened_at_ms: AtomicU64::new(0),
+            epoch: Instant::now(),
+            failure_threshold: failure_threshold.max(1),
+            reset_after,
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match self.state.load(Ordering::Acquire) {
+            OPEN => CircuitState::Open(self.opened_at()),
+            HALF_OPEN => CircuitState::HalfOpen,
+            _ => CircuitState::Closed,
+        }
+    }
+
+    /// Whether a call may proceed; an expired open circuit admits exactly one probe
+    pub fn try_acquire(&self) -> bool {
+        match self.state() {
+            CircuitState::Closed => true,
+            CircuitState::HalfOpen => false,
+            CircuitState::Open(since) => {
+                since.elapsed() >= self.reset_after
+                    && self
+                        .state
+                        .compare_exchange(OPEN, HALF_OPEN, Ordering::AcqRel, Ordering::Acquire)
+                        .is_ok()
+            }
+        }
+    }
+
+    /// Feed back the outcome of an admitted call; client input errors do not count
+    pub fn record<T>(&self, result: &anyhow::Result<T>) {
+        match result {
+            Ok(_) => self.record_success(),
+            Err(e) if is_model_fault(e) => self.record_failure(),
+            Err(_) => self.record_inconclusive(),
+        }
+    }
+
+    /// A probe that hit bad input says nothing about the model, so let the next call probe
+    fn record_inconclusive(&self) {
+        let _ = self
+            .state
+            .compare_exchange(HALF_OPEN, OPEN, Ordering::AcqRel, Ordering::Acquire);
+    }
+
+    fn record_success(&self) {
+        self.failures.store(0, Ordering::Release);
+        if self.state.swap(CLOSED, Ordering::AcqRel) != CLOSED {
+            info!("[DEPTH] Circuit closed, model recovered");
+        }
+    }
+
+    fn record_failure(&self) {
+        let failures = self.failures.fetch_add(1, Ordering::AcqRel) + 1;
+        let probe_failed = self.state.load(Ordering::Acquire) == HALF_OPEN;
+        if probe_failed || failures >= self.failure_threshold {
+            self.open(failures);
+        }
+    }
+
+    fn open(&self, failures: u32) {
+        let now_ms = self.epoch.elapsed().as_millis() as u64;
+        self.opened_at_ms.store(now_ms, Ordering::Release);
+        self.state.store(OPEN, Ordering::Release);
+        warn!(
+            "[DEPTH] Circuit open after {} consecutive failures, retrying in {}s",
+            failures,
+            self.reset_after.as_secs()
+        );
+    }
+
+    fn opened_at(&self) -> Instant {
+        self.epoch + Duration::from_millis(self.opened_at_ms.load(Ordering::Acquire))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::depth::{InvalidFrame, ModelUnavailable};
+
+    fn invalid_frame() -> anyhow::Result<()> {
+        Err(InvalidFrame("cannot identify image file".to_string()).into())
+    }
+
+    #[test]
+    fn decode_errors_do_not_trip_the_breaker() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(30));
+        breaker.record(&invalid_frame());
+        breaker.record::<()>(&Err(ModelUnavailable.into()));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        breaker.record::<()>(&Err(anyhow::anyhow!("simulated crash")));
+        assert!(matches!(breaker.state(), CircuitState::Open(_)));
+    }
+
+    #[test]
+    fn decode_error_on_probe_rearms_the_probe() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record::<()>(&Err(anyhow::anyhow!("simulated crash")));
+        assert!(breaker.try_acquire());
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+        breaker.record(&invalid_frame());
+        assert!(breaker.try_acquire());
+        breaker.record(&Ok(()));
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,123 @@
+use image::{ImageBuffer, Rgb, RgbImage};
+use serde::Deserialize;
+use std::str::FromStr;
+
+use super::depth::DepthMap;
+
+/// Colormaps for visualising depth buffers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Colormap {
+    Viridis,
+    Turbo,
+}
+
+impl FromStr for Colormap {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "viridis" => Ok(Self::Viridis),
+            "turbo" => Ok(Self::Turbo),
+            other => Err(anyhow::anyhow!("Unknown colormap '{}'", other)),
+        }
+    }
+}
+
+/// Degree-6 polynomial fit per channel, coefficients low to high order
+type ChannelPoly = [[f32; 7]; 3];
+
+const VIRIDIS: ChannelPoly = [
+    [
+        0.277_727_33,
+        0.105_093_04,
+        -0.330_861_83,
+        -4.634_230_5,
+        6.228_27,
+        4.776_385,
+        -5.435_456,
+    ],
+    [
+        0.005_407_344_5,
+        1.404_613_5,
+        0.214_847_56,
+        -5.799_101,
+        14.179_933,
+        -13.745_145,
+        4.645_852_6,
+    ],
+    [
+        0.334_099_8,
+        1.384_590_1,
+        0.095_095_16,
+        -19.332_441,
+        56.690_55,
+        -65.353_03,
+        26.312_435,
+    ],
+];
+
+/// Degree-5 fit of Google's Turbo, padded to degree 6
+const TURBO: ChannelPoly = [
+    [
+        0.135_721_38,
+        4.615_392_6,
+        -42.660_323,
+        132.131_08,
+        -152.942_4,
+        59.286_38,
+        0.0,
+    ],
+    [
+        0.091_402_61,
+        2.194_188_4,
+        4.842_966_6,
+        -14.185_033,
+        4.277_299,
+        2.829_566,
+        0.0,
+    ],
+    [
+        0.106_673_3,
+        12.641_946,
+        -60.582_05,
+        110.362_77,
+        -89.903_11,
+        27.348_25,
+        0.0,
+    ],
+];
+
+fn eval(poly: &ChannelPoly, t: f32) -> [u8; 3] {
+    poly.map(|coeffs| {
+        let value = coeffs.iter().rev().fold(0.0, |acc, c| acc * t + c);
+        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    })
+}
+
+impl Colormap {
+    fn poly(&self) -> &'static ChannelPoly {
+        match self {
+            Self::Viridis => &VIRIDIS,
+            Self::Turbo => &TURBO,
+        }
+    }
+
+    /// Colour for a depth value
+    pub fn color(&self, depth: u8) -> [u8; 3] {
+        eval(self.poly(), f32::from(depth) / 255.0)
+    }
+
+    /// Colourise a depth map into an RGB image
+    pub fn apply(&self, depth: &DepthMap) -> RgbImage {
+        let lut: Vec<[u8; 3]> = (0..=u8::MAX).map(|d| self.color(d)).collect();
+        let DepthMap {
+            width,
+            height,
+            pixels,
+        } = depth;
+        ImageBuffer::from_fn(*width, *height, |x, y| {
+            Rgb(lut[pixels[(y * width + x) as usize] as usize])
+        })
+    }
+}
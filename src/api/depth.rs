@@ -1,14 +1,200 @@
+use image::codecs::jpeg::JpegEncoder;
+use image::{ImageFormat, ImageReader};
+use lru::LruCache;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::PyBytes;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::Cursor;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Once};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore, mpsc, oneshot};
 use tracing::{error, info, warn};
 
+use super::circuit_breaker::{CircuitBreaker, CircuitOpen};
+use super::post_process::DepthPostProcessor;
+use crate::config::{DepthConfig, DepthDevice};
+use crate::metrics::metrics;
+
+/// Depth buffer from the estimator: u16 BE width, u16 BE height, then samples
+#[derive(Debug, Clone)]
+pub struct DepthMap<T = u8> {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<T>,
+}
+
+const DEPTH_HEADER_LEN: usize = 4;
+
+/// Split off the dimension header and check the sample count
+fn split_depth_header(bytes: &[u8], sample_len: usize) -> anyhow::Result<(u32, u32, &[u8])> {
+    anyhow::ensure!(
+        bytes.len() >= DEPTH_HEADER_LEN,
+        "Depth buffer too short for header"
+    );
+    let (header, samples) = bytes.split_at(DEPTH_HEADER_LEN);
+    let width = u32::from(u16::from_be_bytes([header[0], header[1]]));
+    let height = u32::from(u16::from_be_bytes([header[2], header[3]]));
+    anyhow::ensure!(
+        samples.len() == (width * height) as usize * sample_len,
+        "Depth buffer has {} bytes, header says {}x{} of {} bytes",
+        samples.len(),
+        width,
+        height,
+        sample_len
+    );
+    Ok((width, height, samples))
+}
+
+/// Dimension header with room reserved for the samples
+fn header_bytes(width: u32, height: u32, sample_bytes: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(DEPTH_HEADER_LEN + sample_bytes);
+    bytes.extend_from_slice(&(width as u16).to_be_bytes());
+    bytes.extend_from_slice(&(height as u16).to_be_bytes());
+    bytes
+}
+
+impl DepthMap {
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let (width, height, pixels) = split_depth_header(bytes, 1)?;
+        Ok(Self {
+            width,
+            height,
+            pixels: pixels.to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let Self {
+            width,
+            height,
+            pixels,
+        } = self;
+        let mut bytes = header_bytes(*width, *height, pixels.len());
+        bytes.extend_from_slice(pixels);
+        bytes
+    }
+}
+
+impl DepthMap<u16> {
+    /// Parse a header followed by little-endian u16 samples
+    pub fn from_le_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let (width, height, samples) = split_depth_header(bytes, 2)?;
+        let pixels = samples
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        let mut bytes = header_bytes(self.width, self.height, self.pixels.len() * 2);
+        self.pixels
+            .iter()
+            .for_each(|p| bytes.extend_from_slice(&p.to_le_bytes()));
+        bytes
+    }
+}
+
+impl DepthMap<f32> {
+    /// Parse a header followed by little-endian f32 samples
+    pub fn from_le_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let (width, height, samples) = split_depth_header(bytes, 4)?;
+        let pixels = samples
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        let mut bytes = header_bytes(self.width, self.height, self.pixels.len() * 4);
+        self.pixels
+            .iter()
+            .for_each(|p| bytes.extend_from_slice(&p.to_le_bytes()));
+        bytes
+    }
+}
+
+/// Sample format of depth results sent over /ws/depth
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DepthFormat {
+    /// Untagged legacy buffer, normalized 0-255
+    #[default]
+    U8,
+    /// Normalized 0-65535
+    U16,
+    /// Raw model output
+    F32,
+}
+
+impl DepthFormat {
+    /// Leading byte on tagged (non-u8) frames
+    pub fn tag(self) -> u8 {
+        match self {
+            Self::U8 => 0,
+            Self::U16 => 1,
+            Self::F32 => 2,
+        }
+    }
+}
+
+/// Side length of the synthetic warm-up frame
+const WARM_UP_SIZE: u32 = 64;
+
+/// GIL waits longer than this are logged
+const GIL_WAIT_WARN: Duration = Duration::from_millis(100);
+
+/// `Python::with_gil` that records how long the GIL was awaited and held
+fn with_gil<F, R>(f: F) -> R
+where
+    F: for<'py> FnOnce(Python<'py>) -> R,
+{
+    let requested = Instant::now();
+    Python::with_gil(|py| {
+        let acquired = Instant::now();
+        let wait = acquired - requested;
+        metrics().gil_wait_duration.observe(wait.as_secs_f64());
+        if wait > GIL_WAIT_WARN {
+            warn!(
+                "[DEPTH] Waited {}ms for the GIL, consider lowering depth.max_concurrent",
+                wait.as_millis()
+            );
+        }
+        let result = f(py);
+        metrics()
+            .gil_held_duration
+            .observe(acquired.elapsed().as_secs_f64());
+        result
+    })
+}
+
 /// Global depth model state
 pub struct DepthModel {
     estimator: PyObject,
+    backend: &'static str,
+    /// Python module the estimator came from, for `reset()` on reload
+    module: &'static str,
+    /// Device requested from config
+    device: DepthDevice,
+    /// Device the estimator reports it settled on
+    active_device: String,
 }
 
 // Safety: PyObject is Send when Python GIL is not held
@@ -38,7 +224,11 @@ fn find_python_dir() -> PathBuf {
 
 /// Setup Python sys.path from PYTHONPATH environment variable
 fn setup_python_path() {
-    Python::with_gil(|py| {
+    // add_python_path already placed PYTHONPATH behind the conda env
+    if std::env::var_os("CONDA_PREFIX").is_some() {
+        return;
+    }
+    with_gil(|py| {
         if let Ok(pythonpath) = std::env::var("PYTHONPATH") {
             let sys = py.import("sys").expect("Failed to import sys");
             let path = sys.getattr("path").expect("Failed to get sys.path");
@@ -59,7 +249,7 @@ fn check_python_deps() -> bool {
     // First ensure PYTHONPATH is applied
     setup_python_path();
 
-    Python::with_gil(|py| {
+    with_gil(|py| {
         // Try importing torch - if it fails, deps aren't installed
         py.import("torch").is_ok()
     })
@@ -123,6 +313,144 @@ fn install_python_deps(python_dir: &Path) -> anyhow::Result<()> {
     ))
 }
 
+/// Env vars DEPTH_PY_ENV must not override
+const PROTECTED_ENV_VARS: [&str; 8] = [
+    "PATH",
+    "LD_PRELOAD",
+    "LD_LIBRARY_PATH",
+    "DYLD_INSERT_LIBRARIES",
+    "DYLD_LIBRARY_PATH",
+    "PYTHONHOME",
+    "PYTHONPATH",
+    "PYTHONSTARTUP",
+];
+
+/// Extra variable injected into the Python process environment
+#[derive(Debug)]
+struct PyEnvVar {
+    key: String,
+    value: String,
+}
+
+/// Parse `KEY=value,KEY2=value2`; a segment without `=` continues the previous value
+fn parse_py_env(spec: &str) -> anyhow::Result<Vec<PyEnvVar>> {
+    let mut vars: Vec<PyEnvVar> = Vec::new();
+    for segment in spec.split(',').filter(|s| !s.is_empty()) {
+        match (segment.split_once('='), vars.last_mut()) {
+            (Some((key, value)), _) => {
+                let key = key.trim();
+                anyhow::ensure!(!key.is_empty(), "Empty variable name in DEPTH_PY_ENV");
+                anyhow::ensure!(
+                    !PROTECTED_ENV_VARS.contains(&key.to_ascii_uppercase().as_str()),
+                    "DEPTH_PY_ENV may not override {}",
+                    key
+                );
+                vars.push(PyEnvVar {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                });
+            }
+            // e.g. CUDA_VISIBLE_DEVICES=0,1
+            (None, Some(previous)) => {
+                previous.value.push(',');
+                previous.value.push_str(segment);
+            }
+            (None, None) => {
+                return Err(anyhow::anyhow!(
+                    "Invalid DEPTH_PY_ENV entry '{}', expected KEY=value",
+                    segment
+                ));
+            }
+        }
+    }
+    Ok(vars)
+}
+
+static PY_ENV_INJECTED: Once = Once::new();
+
+/// Apply DEPTH_PY_ENV before the interpreter reads its environment
+fn inject_python_env() -> anyhow::Result<()> {
+    let Ok(spec) = std::env::var("DEPTH_PY_ENV") else {
+        return Ok(());
+    };
+    let vars = parse_py_env(&spec)?;
+    PY_ENV_INJECTED.call_once(|| {
+        for PyEnvVar { key, value } in vars {
+            // SAFETY: first model load happens at startup before the server
+            // accepts requests, so nothing else touches the environment yet
+            unsafe { std::env::set_var(&key, &value) };
+            info!("[DEPTH] Set {}={} for Python", key, value);
+        }
+    });
+    Ok(())
+}
+
+/// Virtualenvs users commonly create inside python/
+const VENV_DIRS: [&str; 2] = [".venv", "venv"];
+
+static VENV_DETECTED: Once = Once::new();
+
+/// Point DEPTH_SITE_PACKAGES at python/.venv or python/venv unless already set
+fn detect_venv(python_dir: &Path) {
+    if std::env::var_os("DEPTH_SITE_PACKAGES").is_some() {
+        return;
+    }
+    VENV_DETECTED.call_once(|| {
+        if let Some(site_packages) = find_venv_site_packages(python_dir) {
+            // SAFETY: first model load happens at startup before the server
+            // accepts requests, so nothing else touches the environment yet
+            unsafe { std::env::set_var("DEPTH_SITE_PACKAGES", &site_packages) };
+            info!(
+                "[DEPTH] Detected virtualenv site-packages {:?}",
+                site_packages
+            );
+        }
+    });
+}
+
+fn find_venv_site_packages(python_dir: &Path) -> Option<PathBuf> {
+    VENV_DIRS
+        .iter()
+        .find_map(|venv| site_packages_in(&python_dir.join(venv)))
+}
+
+/// lib/python*/site-packages on Unix, Lib/site-packages on Windows
+fn site_packages_in(env_root: &Path) -> Option<PathBuf> {
+    let pattern = env_root.join("lib").join("python*").join("site-packages");
+    let unix = match glob::glob(&pattern.to_string_lossy()) {
+        Ok(paths) => paths.filter_map(Result::ok).find(|path| path.is_dir()),
+        Err(e) => {
+            warn!("[DEPTH] Bad site-packages glob {:?}: {}", pattern, e);
+            None
+        }
+    };
+    unix.or_else(|| Some(env_root.join("Lib").join("site-packages")).filter(|path| path.is_dir()))
+}
+
+/// Log the pyenv version pinned in python/ or the project root
+fn log_pyenv_version(python_dir: &Path) {
+    let candidates = [Some(python_dir), python_dir.parent()];
+    let Some((file, version)) = candidates.into_iter().flatten().find_map(|dir| {
+        let file = dir.join(".python-version");
+        let contents = std::fs::read_to_string(&file).ok()?;
+        let version = contents.lines().next()?.trim().to_string();
+        Some((file, version))
+    }) else {
+        return;
+    };
+    info!("[DEPTH] pyenv Python version {} ({:?})", version, file);
+    let linked = with_gil(|py| py.version().to_string());
+    // Named pyenv virtualenvs carry no version to compare
+    let numeric = version.starts_with(|c: char| c.is_ascii_digit());
+    if numeric && !linked.starts_with(&version) {
+        warn!(
+            "[DEPTH] Embedded interpreter is Python {}, packages installed for {} may not load",
+            linked.split_whitespace().next().unwrap_or(&linked),
+            version
+        );
+    }
+}
+
 /// Setup Python environment and install deps if needed
 fn setup_python_env() -> anyhow::Result<PathBuf> {
     let python_dir = find_python_dir();
@@ -136,6 +464,9 @@ fn setup_python_env() -> anyhow::Result<PathBuf> {
         ));
     }
 
+    detect_venv(&python_dir);
+    log_pyenv_version(&python_dir);
+
     // Add python dir to PYTHONPATH for module imports
     add_python_path(&python_dir);
 
@@ -176,7 +507,7 @@ fn setup_python_env() -> anyhow::Result<PathBuf> {
 
 /// Add python directory and any PYTHONPATH entries to sys.path
 fn add_python_path(python_dir: &PathBuf) {
-    Python::with_gil(|py| {
+    with_gil(|py| {
         if let Ok(sys) = py.import("sys")
             && let Ok(path) = sys.getattr("path")
         {
@@ -201,6 +532,24 @@ fn add_python_path(python_dir: &PathBuf) {
                 }
             }
 
+            // Active conda env goes ahead of PYTHONPATH
+            if let Ok(prefix) = std::env::var("CONDA_PREFIX") {
+                let name = std::env::var("CONDA_DEFAULT_ENV").unwrap_or_else(|_| prefix.clone());
+                match site_packages_in(Path::new(&prefix)) {
+                    Some(site_packages) => {
+                        let _ = path.call_method1("insert", (0, site_packages.to_string_lossy()));
+                        info!(
+                            "[DEPTH] Using conda env {}, added {:?}",
+                            name, site_packages
+                        );
+                    }
+                    None => warn!(
+                        "[DEPTH] Conda env {} has no site-packages under {}",
+                        name, prefix
+                    ),
+                }
+            }
+
             // Add DEPTH_SITE_PACKAGES if set (explicit venv site-packages)
             if let Ok(site_packages) = std::env::var("DEPTH_SITE_PACKAGES") {
                 let _ = path.call_method1("insert", (0, site_packages.as_str()));
@@ -212,11 +561,12 @@ fn add_python_path(python_dir: &PathBuf) {
 
 impl DepthModel {
     /// Initialize the depth model (call once at startup)
-    pub fn new() -> anyhow::Result<Self> {
+    pub fn new(device: DepthDevice) -> anyhow::Result<Self> {
         // Setup environment first
+        inject_python_env()?;
         let python_dir = setup_python_env()?;
 
-        Python::with_gil(|py| {
+        with_gil(|py| {
             // Add python directory to path
             let sys = py.import("sys")?;
             let path = sys.getattr("path")?;
@@ -224,10 +574,16 @@ impl DepthModel {
             path.call_method1("insert", (0, dir_str.as_ref()))?;
 
             // Try ONNX estimator first (much faster), fall back to PyTorch
-            let estimator = match py.import("depth_estimator_onnx") {
+            let (estimator, backend, module) = match py.import("depth_estimator_onnx") {
                 Ok(module) => {
                     info!("[DEPTH] Using ONNX Runtime backend");
-                    module.getattr("DepthEstimator")?.call0()?
+                    (
+                        module
+                            .getattr("DepthEstimator")?
+                            .call1((device.to_string(),))?,
+                        "onnx",
+                        "depth_estimator_onnx",
+                    )
                 }
                 Err(e) => {
                     warn!(
@@ -235,41 +591,179 @@ impl DepthModel {
                         e
                     );
                     let module = py.import("depth_estimator")?;
-                    module.getattr("DepthEstimator")?.call0()?
+                    (
+                        module
+                            .getattr("DepthEstimator")?
+                            .call1((device.to_string(),))?,
+                        "pytorch",
+                        "depth_estimator",
+                    )
                 }
             };
 
-            info!("[DEPTH] Model loaded successfully");
+            // Older estimators may not report a device
+            let active_device = match estimator.getattr("device") {
+                Ok(reported) => reported.str()?.to_string(),
+                Err(_) => device.to_string(),
+            };
+            info!("[DEPTH] Model loaded successfully on {}", active_device);
 
             Ok(Self {
                 estimator: estimator.into(),
+                backend,
+                module,
+                device,
+                active_device,
             })
         })
     }
 
+    /// Run one throwaway inference so operator compilation happens before real traffic
+    pub fn warm_up(&self) -> anyhow::Result<()> {
+        let grey = image::RgbImage::from_pixel(WARM_UP_SIZE, WARM_UP_SIZE, image::Rgb([128; 3]));
+        let mut jpeg = Vec::new();
+        JpegEncoder::new(&mut jpeg).encode_image(&grey)?;
+
+        let start = Instant::now();
+        self.estimate(&jpeg)?;
+        info!(
+            "[DEPTH] Warm-up inference took {}ms",
+            start.elapsed().as_millis()
+        );
+        Ok(())
+    }
+
+    /// Inference backend picked at load time ("onnx" or "pytorch")
+    pub fn backend(&self) -> &str {
+        self.backend
+    }
+
+    /// Device the estimator is running on, as reported by Python
+    pub fn active_device(&self) -> &str {
+        &self.active_device
+    }
+
     /// Run depth inference on JPEG bytes, returns grayscale depth buffer
     pub fn estimate(&self, jpeg_bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
-        Python::with_gil(|py| {
+        self.call_estimator("estimate", jpeg_bytes)
+    }
+
+    /// Wire payload for `format`: legacy buffer for u8, else tag + header + LE samples
+    pub fn estimate_as(&self, format: DepthFormat, jpeg_bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let method = match format {
+            DepthFormat::U8 => return self.estimate(jpeg_bytes),
+            DepthFormat::U16 => "estimate_u16",
+            DepthFormat::F32 => "estimate_f32",
+        };
+        // Python already emits header + LE samples, validate before forwarding
+        let bytes = self.call_estimator(method, jpeg_bytes)?;
+        match format {
+            DepthFormat::U16 => split_depth_header(&bytes, 2)?,
+            _ => split_depth_header(&bytes, 4)?,
+        };
+        let mut payload = Vec::with_capacity(1 + bytes.len());
+        payload.push(format.tag());
+        payload.extend_from_slice(&bytes);
+        Ok(payload)
+    }
+
+    /// Every format goes through here, so one span covers u8, u16 and f32
+    #[tracing::instrument(name = "estimate", skip(self, jpeg_bytes), fields(input_size = jpeg_bytes.len()))]
+    fn call_estimator(&self, method: &str, jpeg_bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+        with_gil(|py| {
             let input = PyBytes::new(py, jpeg_bytes);
-            let result = self.estimator.call_method1(py, "estimate", (input,))?;
+            // Estimators raise ValueError for frames they cannot decode
+            let result = self.estimator.call_method1(py, method, (input,)).map_err(
+                |e| -> anyhow::Error {
+                    if e.is_instance_of::<PyValueError>(py) {
+                        InvalidFrame(e.to_string()).into()
+                    } else {
+                        e.into()
+                    }
+                },
+            )?;
 
             let depth_bytes: Vec<u8> = result.extract(py)?;
             Ok(depth_bytes)
         })
     }
+
+    /// Drop the estimator, then let its module free cached memory (blocking thread only)
+    fn release(self) -> anyhow::Result<()> {
+        with_gil(|py| {
+            let module = self.module;
+            drop(self);
+            let module = py.import(module)?;
+            // Older estimators have no reset()
+            if module.hasattr("reset")? {
+                module.call_method0("reset")?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+impl DepthModel {
+    /// Loaded slot without Python estimator behind it, for route tests
+    pub(crate) fn stub() -> Self {
+        with_gil(|py| Self {
+            estimator: py.None(),
+            backend: "onnx",
+            module: "depth_estimator_onnx",
+            device: DepthDevice::Cpu,
+            active_device: "CPU".to_string(),
+        })
+    }
+}
+
+/// Frame the estimator could not decode, a client error rather than a model fault
+#[derive(Debug)]
+pub struct InvalidFrame(pub(crate) String);
+
+impl fmt::Display for InvalidFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid frame: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidFrame {}
+
+/// No model in the slot: disabled, still loading, or being reloaded
+#[derive(Debug)]
+pub struct ModelUnavailable;
+
+impl fmt::Display for ModelUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Depth model not initialized")
+    }
+}
+
+impl std::error::Error for ModelUnavailable {}
+
+/// Whether an inference error points at the model or interpreter rather than the input
+pub fn is_model_fault(error: &anyhow::Error) -> bool {
+    !error.is::<InvalidFrame>() && !error.is::<ModelUnavailable>()
 }
 
 /// Thread-safe wrapper for the depth model
 pub type SharedDepthModel = Arc<Mutex<Option<DepthModel>>>;
 
 /// Initialize the global depth model
-pub async fn init_depth_model() -> SharedDepthModel {
+#[tracing::instrument]
+pub async fn init_depth_model(device: DepthDevice) -> SharedDepthModel {
     let model = Arc::new(Mutex::new(None));
 
     // Load model in blocking task to not block async runtime
     let model_clone = model.clone();
-    let result = tokio::task::spawn_blocking(move || match DepthModel::new() {
-        Ok(m) => Some(m),
+    let result = tokio::task::spawn_blocking(move || match DepthModel::new(device) {
+        Ok(m) => {
+            // A failed warm-up only costs first-frame latency
+            if let Err(e) = m.warm_up() {
+                warn!("[DEPTH] Warm-up failed: {}", e);
+            }
+            Some(m)
+        }
         Err(e) => {
             error!("[DEPTH] Failed to load model: {}", e);
             error!("[DEPTH] Server depth mode will not be available");
@@ -280,27 +774,470 @@ pub async fn init_depth_model() -> SharedDepthModel {
 
     if let Ok(Some(m)) = result {
         *model_clone.lock().await = Some(m);
+        metrics().depth_model_loaded.set(1);
         info!("[DEPTH] Model initialized and ready");
     }
 
     model
 }
 
-/// Run depth inference (call from WebSocket handler)
-pub async fn run_depth_inference(
+/// Load a fresh model in the background into `slot` (warm standby)
+fn spawn_standby_preload(slot: SharedDepthModel, device: DepthDevice) {
+    tokio::spawn(async move {
+        info!("[DEPTH] Pre-loading standby model");
+        let loaded = tokio::task::spawn_blocking(move || {
+            let m = DepthModel::new(device)?;
+            if let Err(e) = m.warm_up() {
+                warn!("[DEPTH] Standby warm-up failed: {}", e);
+            }
+            anyhow::Ok(m)
+        });
+        match loaded.await {
+            Ok(Ok(m)) => {
+                *slot.lock().await = Some(m);
+                info!("[DEPTH] Standby model ready");
+            }
+            Ok(Err(e)) => error!("[DEPTH] Failed to pre-load standby model: {}", e),
+            Err(e) => error!("[DEPTH] Standby pre-load task failed: {}", e),
+        }
+    });
+}
+
+/// Cleared on a model fault (not bad input) so the watchdog reloads the model
+#[derive(Debug)]
+pub struct ModelHealth(AtomicBool);
+
+impl Default for ModelHealth {
+    fn default() -> Self {
+        Self(AtomicBool::new(true))
+    }
+}
+
+impl ModelHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    fn set(&self, healthy: bool) {
+        self.0.store(healthy, Ordering::Release);
+    }
+}
+
+/// How often the watchdog checks model health
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Reload the model into `model` whenever `health` is cleared, retrying every 10 s
+pub fn spawn_model_watchdog(
+    model: SharedDepthModel,
+    health: Arc<ModelHealth>,
+    device: DepthDevice,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(WATCHDOG_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if health.is_healthy() {
+                continue;
+            }
+            warn!("[DEPTH] Model unhealthy, re-initializing");
+            // Free the faulted model first so the reload starts from clean Python state
+            let retired = model.lock().await.take();
+            metrics().depth_model_loaded.set(0);
+            let loaded = tokio::task::spawn_blocking(move || {
+                if let Some(retired) = retired
+                    && let Err(e) = retired.release()
+                {
+                    warn!("[DEPTH] Releasing the faulted model failed: {}", e);
+                }
+                let m = DepthModel::new(device)?;
+                if let Err(e) = m.warm_up() {
+                    warn!("[DEPTH] Warm-up after re-initialization failed: {}", e);
+                }
+                anyhow::Ok(m)
+            });
+            match loaded.await {
+                Ok(Ok(m)) => {
+                    *model.lock().await = Some(m);
+                    health.set(true);
+                    metrics().depth_model_loaded.set(1);
+                    info!("[DEPTH] Model re-initialized");
+                }
+                Ok(Err(e)) => error!(
+                    "[DEPTH] Re-initialization failed, retrying in {}s: {}",
+                    WATCHDOG_INTERVAL.as_secs(),
+                    e
+                ),
+                Err(e) => error!("[DEPTH] Re-initialization task failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Initialize the standby model slot when warm standby is enabled
+pub fn init_standby_model(enabled: bool, device: DepthDevice) -> Option<SharedDepthModel> {
+    if !enabled {
+        return None;
+    }
+    let standby = Arc::new(Mutex::new(None));
+    spawn_standby_preload(standby.clone(), device);
+    Some(standby)
+}
+
+/// Swap a ready standby model into service and start pre-loading the next one
+pub async fn swap_depth_models(
+    active: &SharedDepthModel,
+    standby: &SharedDepthModel,
+) -> anyhow::Result<()> {
+    // Lock order: active then standby
+    let mut active_guard = active.lock().await;
+    let mut standby_guard = standby.lock().await;
+
+    let Some(ready) = standby_guard.take() else {
+        return Err(anyhow::anyhow!("Standby model is not ready yet"));
+    };
+    let device = ready.device;
+    let retired = active_guard.replace(ready);
+    drop(standby_guard);
+    drop(active_guard);
+
+    // Release the old estimator with the GIL held
+    tokio::task::spawn_blocking(move || with_gil(|_py| drop(retired))).await?;
+
+    info!("[DEPTH] Swapped standby model into service");
+    spawn_standby_preload(standby.clone(), device);
+    Ok(())
+}
+
+/// Lock the model and run one inference (blocking thread only)
+fn estimate_blocking(
     model: &SharedDepthModel,
+    format: DepthFormat,
+    jpeg_bytes: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    let guard = model.blocking_lock();
+
+    match guard.as_ref() {
+        Some(m) => {
+            let start = Instant::now();
+            let result = m.estimate_as(format, jpeg_bytes);
+            let metrics = metrics();
+            metrics.depth_inferences.inc();
+            metrics
+                .depth_inference_duration
+                .observe(start.elapsed().as_secs_f64());
+            result
+        }
+        None => Err(ModelUnavailable.into()),
+    }
+}
+
+/// Frame waiting for the inference worker
+struct DepthJob {
+    format: DepthFormat,
     jpeg_bytes: Vec<u8>,
+    reply: oneshot::Sender<anyhow::Result<Vec<u8>>>,
+    span: tracing::Span,
+}
+
+/// Quality used when transcoding non-JPEG input
+const TRANSCODE_JPEG_QUALITY: u8 = 90;
+
+/// Convert PNG/WebP frames to JPEG and downscale anything over `max_resolution`
+pub fn prepare_frame(
+    frame: Vec<u8>,
+    max_resolution: Option<(u32, u32)>,
 ) -> anyhow::Result<Vec<u8>> {
-    let model = model.clone();
+    let format =
+        image::guess_format(&frame).map_err(|_| anyhow::anyhow!("Unrecognized image format"))?;
+    anyhow::ensure!(
+        matches!(
+            format,
+            ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::WebP
+        ),
+        "Unsupported image format {:?}",
+        format
+    );
+
+    let oversized = match max_resolution {
+        Some((max_w, max_h)) => {
+            let (width, height) =
+                ImageReader::with_format(Cursor::new(&frame), format).into_dimensions()?;
+            width > max_w || height > max_h
+        }
+        None => false,
+    };
+    if format == ImageFormat::Jpeg && !oversized {
+        return Ok(frame);
+    }
+
+    if format != ImageFormat::Jpeg {
+        warn!("[DEPTH] Transcoding {:?} frame to JPEG", format);
+    }
+    let mut image = image::load_from_memory_with_format(&frame, format)?;
+    if let Some((max_w, max_h)) = max_resolution.filter(|_| oversized) {
+        image = image.resize(max_w, max_h, image::imageops::FilterType::Triangle);
+    }
+    let mut jpeg = Vec::new();
+    JpegEncoder::new_with_quality(&mut jpeg, TRANSCODE_JPEG_QUALITY)
+        .encode_image(&image.to_rgb8())?;
+    Ok(jpeg)
+}
+
+/// SHA-256 of the exact frame bytes, so only byte-identical frames share a result
+type CacheKey = ([u8; 32], DepthFormat);
+
+/// Recent results keyed by exact content hash, so repeated frames skip inference
+///
+/// Exact match is deliberate. The cache is shared by every client, and a
+/// perceptual hash (dhash) would hand one client the depth map computed from
+/// another client's similar looking frame, possibly at other dimensions.
+/// The cost is that a re-encoded but visually identical frame misses.
+pub struct DepthCache {
+    entries: std::sync::Mutex<LruCache<CacheKey, Vec<u8>>>,
+}
+
+impl DepthCache {
+    /// None when `capacity` is 0 (cache disabled)
+    pub fn new(capacity: usize) -> Option<Self> {
+        let capacity = NonZeroUsize::new(capacity)?;
+        Some(Self {
+            entries: std::sync::Mutex::new(LruCache::new(capacity)),
+        })
+    }
+
+    fn get(&self, key: CacheKey) -> Option<Vec<u8>> {
+        self.entries
+            .lock()
+            .expect("depth cache lock poisoned")
+            .get(&key)
+            .cloned()
+    }
+
+    fn put(&self, key: CacheKey, depth: Vec<u8>) {
+        self.entries
+            .lock()
+            .expect("depth cache lock poisoned")
+            .put(key, depth);
+    }
+}
+
+/// Bounded queue in front of a single blocking inference worker
+#[derive(Clone)]
+pub struct DepthInferenceQueue {
+    jobs: mpsc::Sender<DepthJob>,
+    cache: Option<Arc<DepthCache>>,
+    /// Caps frames in flight (queued or running) across all clients
+    permits: Arc<Semaphore>,
+    /// Frames one caller can have in flight without overflowing the queue
+    slots: usize,
+    admit_timeout: Duration,
+}
+
+/// No inference slot freed up within the queue timeout
+#[derive(Debug)]
+pub struct DepthBusy;
+
+impl fmt::Display for DepthBusy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "429 depth inference busy")
+    }
+}
+
+impl std::error::Error for DepthBusy {}
+
+/// Admitted frame, counted in depth_queue_depth until dropped
+struct InFlight {
+    _permit: OwnedSemaphorePermit,
+}
 
-    // Run inference in blocking task to not hold GIL on async runtime
-    tokio::task::spawn_blocking(move || {
-        let guard = model.blocking_lock();
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        metrics().depth_queue_depth.dec();
+    }
+}
 
-        match guard.as_ref() {
-            Some(m) => m.estimate(&jpeg_bytes),
-            None => Err(anyhow::anyhow!("Depth model not initialized")),
+impl DepthInferenceQueue {
+    /// Start the worker; it exits once every queue handle is dropped
+    pub fn spawn(
+        model: SharedDepthModel,
+        breaker: Arc<CircuitBreaker>,
+        health: Arc<ModelHealth>,
+        config: &DepthConfig,
+    ) -> Self {
+        // ServerConfig::validate rejects zero sizes and max_concurrent > queue_depth,
+        // so every admitted frame fits the channel
+        let capacity = config.queue_depth;
+        let (jobs, mut rx) = mpsc::channel::<DepthJob>(capacity);
+        let post_processor = DepthPostProcessor::new(config.post_process.clone());
+        tokio::task::spawn_blocking(move || {
+            while let Some(job) = rx.blocking_recv() {
+                let DepthJob {
+                    format,
+                    jpeg_bytes,
+                    reply,
+                    span,
+                } = job;
+                let _entered = span.enter();
+                // Open circuit fails fast without touching the GIL
+                let result = if breaker.try_acquire() {
+                    let result = estimate_blocking(&model, format, &jpeg_bytes);
+                    breaker.record(&result);
+                    if let Err(e) = &result
+                        && is_model_fault(e)
+                    {
+                        health.set(false);
+                    }
+                    // Only u8 maps are post-processed, u16/f32 stay raw
+                    match format {
+                        DepthFormat::U8 => {
+                            result.and_then(|bytes| post_processor.apply_bytes(bytes))
+                        }
+                        DepthFormat::U16 | DepthFormat::F32 => result,
+                    }
+                } else {
+                    Err(CircuitOpen.into())
+                };
+                // Receiver gone means the client left, nothing to report
+                let _ = reply.send(result);
+            }
+            info!("[DEPTH] Inference worker stopped");
+        });
+        info!(
+            "[DEPTH] Inference queue depth {}, max concurrent {}, cache size {}",
+            capacity, config.max_concurrent, config.cache_size
+        );
+        Self {
+            jobs,
+            cache: DepthCache::new(config.cache_size).map(Arc::new),
+            permits: Arc::new(Semaphore::new(config.max_concurrent)),
+            slots: config.max_concurrent.min(capacity),
+            admit_timeout: Duration::from_millis(config.queue_timeout_ms),
         }
-    })
-    .await?
+    }
+
+    /// Frames one caller can have in flight without overflowing the queue
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    /// Wait up to the queue timeout for an in-flight slot
+    async fn admit(&self) -> anyhow::Result<InFlight> {
+        let permit = tokio::time::timeout(self.admit_timeout, self.permits.clone().acquire_owned())
+            .await
+            .map_err(|_| DepthBusy)?
+            .expect("depth semaphore is never closed");
+        metrics().depth_queue_depth.inc();
+        Ok(InFlight { _permit: permit })
+    }
+
+    /// Cache key for a frame, None when caching is off; hands the frame back
+    async fn cache_key(
+        &self,
+        format: DepthFormat,
+        frame: Vec<u8>,
+        max_resolution: Option<(u32, u32)>,
+    ) -> anyhow::Result<(Option<CacheKey>, Vec<u8>)> {
+        if self.cache.is_none() {
+            return Ok((None, frame));
+        }
+        // Hashing a whole frame is CPU work, keep it off the async threads
+        let (digest, frame) = tokio::task::spawn_blocking(move || {
+            let mut hasher = Sha256::new();
+            hasher.update(&frame);
+            // The same frame downscaled differently is a different input
+            if let Some((width, height)) = max_resolution {
+                hasher.update(width.to_le_bytes());
+                hasher.update(height.to_le_bytes());
+            }
+            (hasher.finalize().into(), frame)
+        })
+        .await?;
+        Ok((Some((digest, format)), frame))
+    }
+
+    fn cached(&self, key: Option<CacheKey>) -> Option<Vec<u8>> {
+        let depth = self.cache.as_ref()?.get(key?)?;
+        metrics().depth_cache_hits.inc();
+        Some(depth)
+    }
+
+    fn remember(&self, key: Option<CacheKey>, result: &anyhow::Result<Vec<u8>>) {
+        if let (Some(cache), Some(key), Ok(depth)) = (&self.cache, key, result) {
+            cache.put(key, depth.clone());
+        }
+    }
+
+    /// Convert and enqueue without waiting, errors immediately when the queue is full
+    #[tracing::instrument(skip_all, fields(input_size = frame.len()))]
+    pub async fn try_infer(
+        &self,
+        format: DepthFormat,
+        frame: Vec<u8>,
+        max_resolution: Option<(u32, u32)>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let (key, frame) = self.cache_key(format, frame, max_resolution).await?;
+        if let Some(depth) = self.cached(key) {
+            return Ok(depth);
+        }
+        let _in_flight = self.admit().await?;
+        // Decode only once admitted, so waiting frames cost no CPU
+        let jpeg_bytes =
+            tokio::task::spawn_blocking(move || prepare_frame(frame, max_resolution)).await??;
+
+        let (reply, result) = oneshot::channel();
+        let job = DepthJob {
+            format,
+            jpeg_bytes,
+            reply,
+            span: tracing::Span::current(),
+        };
+        self.jobs.try_send(job).map_err(|e| match e {
+            TrySendError::Full(_) => anyhow::anyhow!("Depth queue full"),
+            TrySendError::Closed(_) => anyhow::anyhow!("Depth worker stopped"),
+        })?;
+        let result = result.await?;
+        self.remember(key, &result);
+        result
+    }
+
+    /// Enqueue, waiting for space when the queue is full
+    #[tracing::instrument(skip_all, fields(input_size = jpeg_bytes.len()))]
+    pub async fn infer(&self, jpeg_bytes: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        let (key, jpeg_bytes) = self.cache_key(DepthFormat::U8, jpeg_bytes, None).await?;
+        if let Some(depth) = self.cached(key) {
+            return Ok(depth);
+        }
+        let _in_flight = self.admit().await?;
+
+        let (reply, result) = oneshot::channel();
+        let job = DepthJob {
+            format: DepthFormat::U8,
+            jpeg_bytes,
+            reply,
+            span: tracing::Span::current(),
+        };
+        self.jobs
+            .send(job)
+            .await
+            .map_err(|_| anyhow::anyhow!("Depth worker stopped"))?;
+        let result = result.await?;
+        self.remember(key, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_model_errors_are_faults() {
+        assert!(!is_model_fault(
+            &InvalidFrame("truncated".to_string()).into()
+        ));
+        assert!(!is_model_fault(&ModelUnavailable.into()));
+        assert!(is_model_fault(&anyhow::anyhow!(
+            "CUDA error: device-side assert"
+        )));
+    }
 }
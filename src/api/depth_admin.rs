@@ -0,0 +1,56 @@
+use axum::{Router, extract::State, http::StatusCode, response::Json, routing::post};
+use serde::Serialize;
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::depth::{SharedDepthModel, swap_depth_models};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct ApiResponse {
+    message: String,
+}
+
+/// Active and standby model slots
+#[derive(Clone)]
+struct DepthSlots {
+    active: SharedDepthModel,
+    standby: SharedDepthModel,
+}
+
+/// Define routes for this endpoint
+/// Path: /admin/depth/swap
+/// Only registered when DEPTH_WARM_STANDBY=1
+pub fn routes(active: SharedDepthModel, standby: SharedDepthModel) -> Router {
+    Router::new()
+        .route("/admin/depth/swap", post(swap_handler))
+        .with_state(DepthSlots { active, standby })
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/depth/swap",
+    tag = "depth",
+    responses(
+        (status = 200, description = "Standby model swapped in", body = ApiResponse),
+        (status = 503, description = "Standby model not ready", body = ApiResponse),
+    )
+)]
+async fn swap_handler(
+    State(slots): State<DepthSlots>,
+) -> Result<Json<ApiResponse>, (StatusCode, Json<ApiResponse>)> {
+    let DepthSlots { active, standby } = slots;
+    match swap_depth_models(&active, &standby).await {
+        Ok(()) => Ok(Json(ApiResponse {
+            message: "Standby model swapped in, pre-loading next standby".to_string(),
+        })),
+        Err(e) => {
+            warn!("[DEPTH] Swap rejected: {}", e);
+            Err((
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(ApiResponse {
+                    message: e.to_string(),
+                }),
+            ))
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use axum::{
+    Router,
+    body::Bytes,
+    extract::{DefaultBodyLimit, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use tracing::{error, warn};
+
+use super::depth::{DepthBusy, DepthInferenceQueue, InvalidFrame};
+
+/// Largest JPEG accepted over HTTP
+const MAX_UPLOAD_BYTES: usize = 16 * 1024 * 1024;
+
+/// Define routes for this endpoint
+/// Path: /api/depth/estimate
+/// HTTP alternative to /ws/depth for one-off frames
+pub fn routes(queue: DepthInferenceQueue) -> Router {
+    Router::new()
+        .route("/api/depth/estimate", post(handler))
+        .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES))
+        .with_state(queue)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/depth/estimate",
+    tag = "depth",
+    request_body(content = Vec<u8>, content_type = "image/jpeg", description = "JPEG frame"),
+    responses(
+        (status = 200, description = "u16 BE width, u16 BE height, then u8 depth pixels", content_type = "application/octet-stream"),
+        (status = 400, description = "Empty body or undecodable frame"),
+        (status = 401, description = "JWT required and missing or invalid"),
+        (status = 429, description = "No inference slot freed up within the queue timeout"),
+        (status = 503, description = "Depth model unavailable or inference failed"),
+    )
+)]
+async fn handler(
+    State(queue): State<DepthInferenceQueue>,
+    jpeg: Bytes,
+) -> Result<Response, StatusCode> {
+    if jpeg.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    match queue.infer(jpeg.to_vec()).await {
+        Ok(depth) => {
+            Ok(([(header::CONTENT_TYPE, "application/octet-stream")], depth).into_response())
+        }
+        Err(e) if e.is::<DepthBusy>() => Err(StatusCode::TOO_MANY_REQUESTS),
+        Err(e) if e.is::<InvalidFrame>() => {
+            warn!("[DEPTH] Rejected frame: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(e) => {
+            error!("[DEPTH] HTTP inference error: {}", e);
+            Err(StatusCode::SERVICE_UNAVAILABLE)
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use axum::{
+    Router,
+    extract::{DefaultBodyLimit, Multipart, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use image::ImageFormat;
+use std::io::Cursor;
+use tracing::{error, warn};
+
+use super::colormap::Colormap;
+use super::depth::{DepthBusy, DepthInferenceQueue, DepthMap, InvalidFrame};
+
+/// Largest multipart upload accepted
+const MAX_UPLOAD_BYTES: usize = 16 * 1024 * 1024;
+
+/// Form field carrying the JPEG frame
+const FRAME_FIELD: &str = "frame";
+
+/// Define routes for this endpoint
+/// Path: /api/depth/preview
+/// Turbo-coloured PNG of the depth result, for eyeballing model output
+pub fn routes(queue: DepthInferenceQueue) -> Router {
+    Router::new()
+        .route("/api/depth/preview", post(handler))
+        .layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES))
+        .with_state(queue)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/depth/preview",
+    tag = "depth",
+    request_body(content_type = "multipart/form-data", description = "JPEG frame in the \"frame\" field"),
+    responses(
+        (status = 200, description = "Depth map coloured with the Turbo colormap", content_type = "image/png"),
+        (status = 400, description = "Missing, empty or undecodable frame field"),
+        (status = 401, description = "JWT required and missing or invalid"),
+        (status = 429, description = "No inference slot freed up within the queue timeout"),
+        (status = 503, description = "Depth model unavailable or inference failed"),
+    )
+)]
+async fn handler(
+    State(queue): State<DepthInferenceQueue>,
+    multipart: Multipart,
+) -> Result<Response, StatusCode> {
+    let jpeg = read_frame(multipart).await?;
+
+    let depth_bytes = match queue.infer(jpeg).await {
+        Ok(depth_bytes) => depth_bytes,
+        Err(e) if e.is::<DepthBusy>() => return Err(StatusCode::TOO_MANY_REQUESTS),
+        Err(e) if e.is::<InvalidFrame>() => {
+            warn!("[DEPTH] Rejected frame: {}", e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        Err(e) => {
+            error!("[DEPTH] Preview inference error: {}", e);
+            return Err(StatusCode::SERVICE_UNAVAILABLE);
+        }
+    };
+
+    // Colourising and PNG encoding are CPU-bound
+    let png = tokio::task::spawn_blocking(move || render_png(&depth_bytes))
+        .await
+        .map_err(|e| {
+            error!("[DEPTH] Preview render task failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .map_err(|e| {
+            error!("[DEPTH] Preview render failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response())
+}
+
+/// Pull the JPEG out of the "frame" field
+async fn read_frame(mut multipart: Multipart) -> Result<Vec<u8>, StatusCode> {
+    loop {
+        let field = multipart.next_field().await.map_err(|e| {
+            warn!("[DEPTH] Bad preview upload: {}", e);
+            StatusCode::BAD_REQUEST
+        })?;
+        let Some(field) = field else {
+            return Err(StatusCode::BAD_REQUEST);
+        };
+        if field.name() != Some(FRAME_FIELD) {
+            continue;
+        }
+        let bytes = field.bytes().await.map_err(|e| {
+            warn!("[DEPTH] Bad preview upload: {}", e);
+            StatusCode::BAD_REQUEST
+        })?;
+        return if bytes.is_empty() {
+            Err(StatusCode::BAD_REQUEST)
+        } else {
+            Ok(bytes.to_vec())
+        };
+    }
+}
+
+fn render_png(depth_bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let depth = DepthMap::from_bytes(depth_bytes)?;
+    let mut png = Cursor::new(Vec::new());
+    Colormap::Turbo
+        .apply(&depth)
+        .write_to(&mut png, ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
@@ -0,0 +1,211 @@
+use axum::{
+    Router,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+    routing::get,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::error;
+use utoipa::ToSchema;
+
+use super::circuit_breaker::{CircuitBreaker, CircuitState};
+use super::depth::SharedDepthModel;
+use super::memory::rss_bytes;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct HealthResponse {
+    status: &'static str,
+    /// Depth model circuit breaker state, readiness only and omitted with depth disabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    circuit: Option<&'static str>,
+    /// Resident set size, omitted when it cannot be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rss_mb: Option<u64>,
+}
+
+#[derive(Clone)]
+struct ReadyState {
+    /// Off with --no-depth: ready without a model
+    depth_enabled: bool,
+    model: SharedDepthModel,
+    breaker: Arc<CircuitBreaker>,
+    memory_limit_mb: Option<u64>,
+}
+
+/// Define routes for this endpoint
+/// Path: /healthz
+/// Liveness probe for orchestrators
+pub fn liveness_routes() -> Router {
+    Router::new().route("/healthz", get(healthz))
+}
+
+/// Define routes for this endpoint
+/// Path: /readyz
+/// Readiness probe for orchestrators
+pub fn readiness_routes(
+    depth_enabled: bool,
+    model: SharedDepthModel,
+    breaker: Arc<CircuitBreaker>,
+    memory_limit_mb: Option<u64>,
+) -> Router {
+    Router::new()
+        .route("/readyz", get(readyz))
+        .with_state(ReadyState {
+            depth_enabled,
+            model,
+            breaker,
+            memory_limit_mb,
+        })
+}
+
+/// RSS in MiB, logged and skipped if unreadable
+fn rss_mb() -> Option<u64> {
+    match rss_bytes() {
+        Ok(rss) => Some(rss / (1024 * 1024)),
+        Err(e) => {
+            error!("[HEALTH] Reading RSS failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Process is up
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    responses((status = 200, description = "Process is up", body = HealthResponse))
+)]
+async fn healthz() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok",
+        circuit: None,
+        rss_mb: rss_mb(),
+    })
+}
+
+/// Ready once the depth model is loaded (or depth is disabled), its circuit is not open and RSS is under the limit
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    responses(
+        (status = 200, description = "Model loaded and circuit closed, or depth disabled", body = HealthResponse),
+        (status = 503, description = "Model loading, circuit open or RSS over memory_limit_mb", body = HealthResponse),
+    )
+)]
+async fn readyz(State(state): State<ReadyState>) -> Response {
+    let ReadyState {
+        depth_enabled,
+        model,
+        breaker,
+        memory_limit_mb,
+    } = state;
+    // Held lock: inference or a swap, both with a model loaded, or an instant empty-slot
+    // check or watchdog handoff. Report loaded; a wrong answer lasts microseconds
+    let loaded = match model.try_lock() {
+        Ok(guard) => guard.is_some(),
+        Err(_) => true,
+    };
+    let circuit = breaker.state();
+    let rss_mb = rss_mb();
+    let over_limit = match (rss_mb, memory_limit_mb) {
+        (Some(rss), Some(limit)) => rss > limit,
+        _ => false,
+    };
+    let (code, status) = match (depth_enabled, loaded, circuit) {
+        (true, false, _) => (StatusCode::SERVICE_UNAVAILABLE, "loading"),
+        _ if over_limit => (StatusCode::SERVICE_UNAVAILABLE, "memory_limit"),
+        // The slot stays empty for good, nothing to wait for
+        (false, _, _) => (StatusCode::OK, "disabled"),
+        (true, true, CircuitState::Open(_)) => (StatusCode::SERVICE_UNAVAILABLE, "degraded"),
+        (true, true, CircuitState::Closed | CircuitState::HalfOpen) => (StatusCode::OK, "ok"),
+    };
+    let circuit = if depth_enabled {
+        Some(circuit.as_str())
+    } else {
+        None
+    };
+    (
+        code,
+        Json(HealthResponse {
+            status,
+            circuit,
+            rss_mb,
+        }),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::depth::DepthModel;
+    use axum::{body::Body, http::Request};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    async fn get(app: Router, path: &str) -> (StatusCode, serde_json::Value) {
+        let request = Request::get(path).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn healthz_is_ok() {
+        let (status, body) = get(liveness_routes(), "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn readyz_turns_ready_once_the_model_loads() {
+        let model = SharedDepthModel::default();
+        let app = readiness_routes(true, model.clone(), Arc::default(), None);
+
+        let (status, body) = get(app.clone(), "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "loading");
+
+        *model.lock().await = Some(DepthModel::stub());
+        let (status, body) = get(app, "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["circuit"], "closed");
+    }
+
+    #[tokio::test]
+    async fn readyz_is_ready_with_depth_disabled() {
+        let app = readiness_routes(false, SharedDepthModel::default(), Arc::default(), None);
+
+        let (status, body) = get(app, "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "disabled");
+        assert!(body.get("circuit").is_none());
+    }
+
+    #[tokio::test]
+    async fn readyz_recovers_when_the_circuit_closes() {
+        let model = SharedDepthModel::default();
+        *model.lock().await = Some(DepthModel::stub());
+        let breaker = Arc::new(CircuitBreaker::new(1, Duration::ZERO));
+        let app = readiness_routes(true, model, breaker.clone(), None);
+
+        breaker.record::<()>(&Err(anyhow::anyhow!("simulated crash")));
+        let (status, body) = get(app.clone(), "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "degraded");
+
+        assert!(breaker.try_acquire());
+        breaker.record(&Ok(()));
+        let (status, body) = get(app, "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["circuit"], "closed");
+    }
+}
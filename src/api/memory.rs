@@ -0,0 +1,31 @@
+/// Resident set size of this process, Python heap included
+#[cfg(target_os = "linux")]
+pub fn rss_bytes() -> anyhow::Result<u64> {
+    let status = std::fs::read_to_string("/proc/self/status")?;
+    let kb = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.trim().strip_suffix("kB"))
+        .ok_or_else(|| anyhow::anyhow!("No VmRSS line in /proc/self/status"))?
+        .trim()
+        .parse::<u64>()?;
+    Ok(kb * 1024)
+}
+
+/// Resident set size of this process, Python heap included
+#[cfg(not(target_os = "linux"))]
+pub fn rss_bytes() -> anyhow::Result<u64> {
+    use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+    let pid = sysinfo::get_current_pid().map_err(|e| anyhow::anyhow!(e))?;
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        false,
+        ProcessRefreshKind::nothing().with_memory(),
+    );
+    system
+        .process(pid)
+        .map(|process| process.memory())
+        .ok_or_else(|| anyhow::anyhow!("Own process {} not found", pid))
+}
@@ -1,8 +1,21 @@
 // API route modules - each file defines routes for its endpoint
+pub mod circuit_breaker;
+pub mod colormap;
 pub mod create;
 pub mod depth;
+pub mod depth_admin;
+pub mod depth_estimate;
+pub mod depth_preview;
 pub mod env;
 pub mod greet;
+pub mod health;
 pub mod hello;
+pub mod memory;
+pub mod model_integrity;
+pub mod models;
+pub mod openapi;
+pub mod post_process;
 pub mod search;
+pub mod temporal;
+pub mod version;
 pub mod ws_depth;
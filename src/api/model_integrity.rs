@@ -0,0 +1,239 @@
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::path::Path;
+use tracing::{error, info, warn};
+
+/// `sha256sum` output stored next to the models
+const CHECKSUM_FILE: &str = "checksums.sha256";
+
+/// One `<hex>  <file>` line of the checksum file
+struct Checksum {
+    sha256: String,
+    file: String,
+}
+
+/// Parse `sha256sum` output; a `*` before the name marks binary mode
+fn parse_checksums(contents: &str) -> anyhow::Result<Vec<Checksum>> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (sha256, file) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| anyhow::anyhow!("Bad {} line '{}'", CHECKSUM_FILE, line))?;
+            let file = file.trim_start();
+            let file = file.strip_prefix('*').unwrap_or(file);
+            anyhow::ensure!(
+                sha256.len() == 64 && sha256.bytes().all(|b| b.is_ascii_hexdigit()),
+                "Bad SHA-256 '{}' for {} in {}",
+                sha256,
+                file,
+                CHECKSUM_FILE
+            );
+            anyhow::ensure!(
+                !file.contains(['/', '\\']) && file != "..",
+                "{} may only name files in the models directory, got '{}'",
+                CHECKSUM_FILE,
+                file
+            );
+            Ok(Checksum {
+                sha256: sha256.to_ascii_lowercase(),
+                file: file.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Hex SHA-256 of a file, streamed
+fn sha256_file(path: &Path) -> anyhow::Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Compare one file against its expected digest, logging any mismatch
+fn matches(models_dir: &Path, checksum: &Checksum) -> bool {
+    let Checksum { sha256, file } = checksum;
+    match sha256_file(&models_dir.join(file)) {
+        Ok(actual) if actual == *sha256 => true,
+        Ok(actual) => {
+            error!(
+                "[MODELS] {} checksum mismatch: expected {}, got {}",
+                file, sha256, actual
+            );
+            false
+        }
+        Err(e) => {
+            error!("[MODELS] {} could not be hashed: {}", file, e);
+            false
+        }
+    }
+}
+
+/// Fetch `<base_url>/<file>` next to the models, then move it into place (blocking thread only)
+#[cfg(feature = "model-download")]
+fn download_model(base_url: &str, models_dir: &Path, file: &str) -> anyhow::Result<()> {
+    let url = format!("{}/{}", base_url.trim_end_matches('/'), file);
+    info!("[MODELS] Re-downloading {} from {}", file, url);
+    let mut response = reqwest::blocking::get(&url)?.error_for_status()?;
+    let partial = models_dir.join(format!("{}.part", file));
+    let copied = File::create(&partial)
+        .map_err(anyhow::Error::from)
+        .and_then(|mut part| Ok(response.copy_to(&mut part)?));
+    if let Err(e) = copied {
+        // Never leave a truncated download behind
+        if let Err(remove) = std::fs::remove_file(&partial)
+            && remove.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!("[MODELS] Could not remove {:?}: {}", partial, remove);
+        }
+        return Err(e);
+    }
+    std::fs::rename(&partial, models_dir.join(file))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "model-download"))]
+fn download_model(_base_url: &str, _models_dir: &Path, _file: &str) -> anyhow::Result<()> {
+    anyhow::bail!("built without the model-download feature")
+}
+
+/// Check every .onnx file listed in checksums.sha256, re-downloading
+/// mismatches from MODEL_DOWNLOAD_URL when set (blocking thread only)
+pub fn verify_model_checksums(models_dir: &Path) -> anyhow::Result<()> {
+    let base_url = std::env::var("MODEL_DOWNLOAD_URL").ok();
+    verify_checksums(models_dir, base_url.as_deref())
+}
+
+fn verify_checksums(models_dir: &Path, base_url: Option<&str>) -> anyhow::Result<()> {
+    let contents = match std::fs::read_to_string(models_dir.join(CHECKSUM_FILE)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            info!(
+                "[MODELS] No {} in {:?}, skipping integrity check",
+                CHECKSUM_FILE, models_dir
+            );
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let checksums = parse_checksums(&contents)?;
+
+    let models: Vec<&Checksum> = checksums
+        .iter()
+        .filter(|checksum| checksum.file.ends_with(".onnx"))
+        .collect();
+    let failed: Vec<&Checksum> = models
+        .iter()
+        .copied()
+        .filter(|checksum| !matches(models_dir, checksum))
+        .collect();
+    if failed.is_empty() {
+        info!("[MODELS] Verified {} model checksum(s)", models.len());
+        return Ok(());
+    }
+
+    let Some(base_url) = base_url else {
+        anyhow::bail!(
+            "{} model file(s) failed verification, set MODEL_DOWNLOAD_URL to re-download",
+            failed.len()
+        );
+    };
+    for checksum in &failed {
+        if let Err(e) = download_model(base_url, models_dir, &checksum.file) {
+            warn!("[MODELS] Download of {} failed: {}", checksum.file, e);
+        }
+    }
+    let still_failed = failed
+        .iter()
+        .filter(|checksum| !matches(models_dir, checksum))
+        .count();
+    anyhow::ensure!(
+        still_failed == 0,
+        "{} model file(s) still fail verification after re-download",
+        still_failed
+    );
+    info!("[MODELS] Re-downloaded {} model file(s)", failed.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn models_dir(checksums: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("good.onnx"), "hello").unwrap();
+        std::fs::write(dir.path().join("bad.onnx"), "tampered").unwrap();
+        std::fs::write(dir.path().join(CHECKSUM_FILE), checksums).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parses_sha256sum_output() {
+        let upper = HELLO_SHA256.to_ascii_uppercase();
+        let contents = format!(
+            "# generated\n\n{}  good.onnx\n{} *binary.onnx\n",
+            HELLO_SHA256, upper
+        );
+        let checksums = parse_checksums(&contents).unwrap();
+        let parsed: Vec<(&str, &str)> = checksums
+            .iter()
+            .map(|Checksum { sha256, file }| (sha256.as_str(), file.as_str()))
+            .collect();
+        assert_eq!(
+            parsed,
+            [(HELLO_SHA256, "good.onnx"), (HELLO_SHA256, "binary.onnx")]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for line in [
+            "nodigest".to_string(),
+            "abc123  short.onnx".to_string(),
+            format!("{}  ../escape.onnx", HELLO_SHA256),
+            format!("{}  sub/dir.onnx", HELLO_SHA256),
+        ] {
+            assert!(parse_checksums(&line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn matching_models_pass() {
+        // Non-model entries are listed but never checked
+        let checksums = format!("{}  good.onnx\n{}  README.md\n", HELLO_SHA256, HELLO_SHA256);
+        let dir = models_dir(&checksums);
+        verify_checksums(dir.path(), None).unwrap();
+    }
+
+    #[test]
+    fn mismatched_or_missing_models_fail() {
+        let checksums = format!(
+            "{}  good.onnx\n{}  bad.onnx\n{}  missing.onnx\n",
+            HELLO_SHA256, HELLO_SHA256, HELLO_SHA256
+        );
+        let dir = models_dir(&checksums);
+        let error = verify_checksums(dir.path(), None).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "2 model file(s) failed verification, set MODEL_DOWNLOAD_URL to re-download"
+        );
+    }
+
+    #[test]
+    fn missing_checksum_file_skips_the_check() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("model.onnx"), "anything").unwrap();
+        verify_checksums(dir.path(), None).unwrap();
+    }
+
+    #[test]
+    fn malformed_checksum_file_is_an_error() {
+        let dir = models_dir("not a checksum\n");
+        assert!(verify_checksums(dir.path(), None).is_err());
+    }
+}
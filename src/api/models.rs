@@ -0,0 +1,275 @@
+use axum::{
+    Router,
+    body::Body,
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+use tracing::{error, info};
+
+/// Inclusive byte range requested via the `Range` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ByteRange {
+    start: u64,
+    end: u64,
+}
+
+impl ByteRange {
+    fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// Outcome of parsing a `Range` header against a file length
+#[derive(Debug, PartialEq, Eq)]
+enum RangeRequest {
+    Full,
+    Partial(ByteRange),
+    Unsatisfiable,
+}
+
+/// Parse a single `bytes=start-end` range (multipart ranges are served in full)
+/// Invalid syntax is ignored like a missing header (RFC 9110 14.2), only a
+/// well-formed range outside the file is unsatisfiable
+fn parse_range(headers: &HeaderMap, file_len: u64) -> RangeRequest {
+    let Some(value) = headers.get(header::RANGE).and_then(|v| v.to_str().ok()) else {
+        return RangeRequest::Full;
+    };
+    let Some(spec) = value.strip_prefix("bytes=") else {
+        return RangeRequest::Full;
+    };
+    if spec.contains(',') {
+        return RangeRequest::Full;
+    }
+    let Some((start, end)) = spec.split_once('-') else {
+        return RangeRequest::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+
+    let range = match (start.parse::<u64>(), end.parse::<u64>()) {
+        // bytes=start-end
+        (Ok(start), Ok(end)) if start <= end => ByteRange {
+            start,
+            end: end.min(file_len.saturating_sub(1)),
+        },
+        // bytes=start-
+        (Ok(start), Err(_)) if end.is_empty() => ByteRange {
+            start,
+            end: file_len.saturating_sub(1),
+        },
+        // bytes=-0 asks for nothing
+        (Err(_), Ok(0)) if start.is_empty() => return RangeRequest::Unsatisfiable,
+        // bytes=-suffix_len
+        (Err(_), Ok(suffix)) if start.is_empty() => ByteRange {
+            start: file_len.saturating_sub(suffix),
+            end: file_len.saturating_sub(1),
+        },
+        _ => return RangeRequest::Full,
+    };
+
+    if file_len == 0 || range.start >= file_len {
+        return RangeRequest::Unsatisfiable;
+    }
+    RangeRequest::Partial(range)
+}
+
+/// Strong validator from file size and modification time (like nginx), so If-Range can resume downloads
+fn compute_etag(metadata: &std::fs::Metadata) -> String {
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!("\"{:x}-{:x}\"", metadata.len(), mtime)
+}
+
+/// If-None-Match uses weak comparison: `*`, or any listed tag ignoring `W/`
+fn none_match(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.trim() == "*"
+        || if_none_match
+            .split(',')
+            .map(|tag| tag.trim())
+            .any(|tag| tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+/// Define routes for this endpoint
+/// Path: /api/models/:name/download
+/// Streams ONNX model files with Range support for resumable downloads
+pub fn routes(models_dir: PathBuf) -> Router {
+    Router::new()
+        .route("/api/models/:name/download", get(handler))
+        .with_state(models_dir)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/models/{name}/download",
+    tag = "models",
+    params(("name" = String, Path, description = "Model file name in the models directory")),
+    responses(
+        (status = 200, description = "Full model file", content_type = "application/octet-stream"),
+        (status = 206, description = "Requested byte range", content_type = "application/octet-stream"),
+        (status = 304, description = "ETag matched If-None-Match"),
+        (status = 400, description = "Name is not a plain file name"),
+        (status = 404, description = "Model not found"),
+        (status = 416, description = "Range not satisfiable"),
+    )
+)]
+async fn handler(
+    State(models_dir): State<PathBuf>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    // Reject anything that is not a plain file name (no traversal)
+    if std::path::Path::new(&name).file_name() != Some(name.as_ref()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let path = models_dir.join(&name);
+    let mut file = tokio::fs::File::open(&path)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    let metadata = file.metadata().await.map_err(|e| {
+        error!("[MODELS] Failed to stat {:?}: {}", path, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !metadata.is_file() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let file_len = metadata.len();
+    let etag = compute_etag(&metadata);
+    let etag_value = HeaderValue::from_str(&etag).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| none_match(v, &etag));
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag_value)]).into_response());
+    }
+
+    // Only honour Range when If-Range is absent or still matches; strong comparison, so W/ never does
+    let if_range_matches = headers
+        .get(header::IF_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|v| v == etag);
+    let range = if if_range_matches {
+        parse_range(&headers, file_len)
+    } else {
+        RangeRequest::Full
+    };
+
+    let response = Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, etag_value);
+
+    let response = match range {
+        RangeRequest::Full => {
+            info!("[MODELS] Streaming {} ({} bytes)", name, file_len);
+            response
+                .status(StatusCode::OK)
+                .header(header::CONTENT_LENGTH, file_len)
+                .body(Body::from_stream(ReaderStream::new(file)))
+        }
+        RangeRequest::Partial(range) => {
+            info!(
+                "[MODELS] Streaming {} bytes {}-{}/{}",
+                name, range.start, range.end, file_len
+            );
+            file.seek(SeekFrom::Start(range.start)).await.map_err(|e| {
+                error!("[MODELS] Failed to seek {:?}: {}", path, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            response
+                .status(StatusCode::PARTIAL_CONTENT)
+                .header(header::CONTENT_LENGTH, range.len())
+                .header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", range.start, range.end, file_len),
+                )
+                .body(Body::from_stream(ReaderStream::new(file.take(range.len()))))
+        }
+        RangeRequest::Unsatisfiable => response
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", file_len))
+            .body(Body::empty()),
+    };
+
+    response.map_err(|e| {
+        error!("[MODELS] Failed to build response: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(value: &str, file_len: u64) -> RangeRequest {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RANGE, HeaderValue::from_str(value).unwrap());
+        parse_range(&headers, file_len)
+    }
+
+    fn partial(start: u64, end: u64) -> RangeRequest {
+        RangeRequest::Partial(ByteRange { start, end })
+    }
+
+    #[test]
+    fn range_forms_are_parsed() {
+        assert_eq!(range("bytes=0-99", 1000), partial(0, 99));
+        assert_eq!(range("bytes=500-", 1000), partial(500, 999));
+        assert_eq!(range("bytes=-100", 1000), partial(900, 999));
+        assert_eq!(range("bytes= 10 - 19 ", 1000), partial(10, 19));
+        assert_eq!(parse_range(&HeaderMap::new(), 1000), RangeRequest::Full);
+    }
+
+    #[test]
+    fn range_is_clamped_to_the_file() {
+        assert_eq!(range("bytes=900-5000", 1000), partial(900, 999));
+        assert_eq!(range("bytes=-5000", 1000), partial(0, 999));
+    }
+
+    #[test]
+    fn range_outside_the_file_is_unsatisfiable() {
+        assert_eq!(range("bytes=1000-", 1000), RangeRequest::Unsatisfiable);
+        assert_eq!(range("bytes=1000-1010", 1000), RangeRequest::Unsatisfiable);
+        assert_eq!(range("bytes=-0", 1000), RangeRequest::Unsatisfiable);
+        assert_eq!(range("bytes=0-", 0), RangeRequest::Unsatisfiable);
+        assert_eq!(range("bytes=-10", 0), RangeRequest::Unsatisfiable);
+    }
+
+    #[test]
+    fn invalid_or_multi_range_is_served_in_full() {
+        for value in [
+            "bytes=abc-",
+            "bytes=-abc",
+            "bytes=-",
+            "bytes=5",
+            "bytes=20-10",
+            "items=0-10",
+            "bytes=0-10,20-30",
+        ] {
+            assert_eq!(range(value, 1000), RangeRequest::Full, "{}", value);
+        }
+    }
+
+    #[test]
+    fn if_none_match_compares_weakly() {
+        let etag = "\"10-5\"";
+        assert!(none_match("\"10-5\"", etag));
+        assert!(none_match("W/\"10-5\"", etag));
+        assert!(none_match("\"1-1\", W/\"10-5\"", etag));
+        assert!(none_match("*", etag));
+        assert!(!none_match("\"10-6\"", etag));
+    }
+}
@@ -0,0 +1,51 @@
+use axum::{Router, response::Json, routing::get};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::{Config, SwaggerUi};
+
+use super::{depth_admin, depth_estimate, depth_preview, health, models, version, ws_depth};
+use crate::metrics;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "DepthXR",
+        description = "Depth inference and model serving API"
+    ),
+    paths(
+        models::handler,
+        depth_admin::swap_handler,
+        depth_estimate::handler,
+        depth_preview::handler,
+        ws_depth::ws_depth_handler,
+        health::healthz,
+        health::readyz,
+        version::handler,
+        metrics::handler,
+        spec_handler,
+    ),
+    components(schemas(
+        depth_admin::ApiResponse,
+        health::HealthResponse,
+        version::VersionResponse
+    ))
+)]
+struct ApiDoc;
+
+/// Define routes for this endpoint
+/// Path: /api/openapi.json, /api/docs
+/// Swagger UI assets are embedded at build time, so the docs work offline
+pub fn routes() -> Router {
+    Router::new()
+        .route("/api/openapi.json", get(spec_handler))
+        .merge(SwaggerUi::new("/api/docs").config(Config::from("/api/openapi.json")))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/openapi.json",
+    tag = "docs",
+    responses((status = 200, description = "OpenAPI 3 specification", content_type = "application/json"))
+)]
+async fn spec_handler() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
@@ -0,0 +1,139 @@
+use super::depth::DepthMap;
+use crate::config::PostProcessConfig;
+
+/// Histogram tails clipped by the contrast stretch
+const STRETCH_CLIP_PERCENT: u32 = 1;
+
+/// Median filter, then contrast stretch and invert folded into one lookup table
+#[derive(Debug, Clone)]
+pub struct DepthPostProcessor {
+    config: PostProcessConfig,
+}
+
+impl DepthPostProcessor {
+    pub fn new(config: PostProcessConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn is_noop(&self) -> bool {
+        let PostProcessConfig {
+            contrast_stretch,
+            median_filter_radius,
+            invert,
+        } = &self.config;
+        !contrast_stretch && median_filter_radius.is_none_or(|r| r == 0) && !invert
+    }
+
+    /// Process an estimator buffer (header + u8 pixels)
+    pub fn apply_bytes(&self, bytes: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        if self.is_noop() {
+            return Ok(bytes);
+        }
+        let mut map = DepthMap::from_bytes(&bytes)?;
+        self.apply(&mut map);
+        Ok(map.to_bytes())
+    }
+
+    pub fn apply(&self, map: &mut DepthMap) {
+        let PostProcessConfig {
+            contrast_stretch,
+            median_filter_radius,
+            invert,
+        } = &self.config;
+
+        if let Some(radius) = median_filter_radius.filter(|r| *r > 0) {
+            map.pixels = median_filter(map, usize::from(radius));
+        }
+
+        let mut lut: [u8; 256] = std::array::from_fn(|v| v as u8);
+        if *contrast_stretch {
+            lut = stretch_lut(&map.pixels);
+        }
+        if *invert {
+            lut.iter_mut().for_each(|v| *v = 255 - *v);
+        }
+        // Table lookup over a flat slice, no per-pixel branching
+        if *contrast_stretch || *invert {
+            map.pixels
+                .iter_mut()
+                .for_each(|p| *p = lut[usize::from(*p)]);
+        }
+    }
+}
+
+fn histogram(pixels: &[u8]) -> [u32; 256] {
+    let mut hist = [0u32; 256];
+    pixels.iter().for_each(|p| hist[usize::from(*p)] += 1);
+    hist
+}
+
+/// Smallest value whose cumulative count exceeds `target`
+fn nth_value(hist: &[u32; 256], target: u32) -> u8 {
+    let mut seen = 0;
+    for (value, count) in hist.iter().enumerate() {
+        seen += count;
+        if seen > target {
+            return value as u8;
+        }
+    }
+    255
+}
+
+/// Map the 1st-99th percentile range onto 0-255
+fn stretch_lut(pixels: &[u8]) -> [u8; 256] {
+    let identity = std::array::from_fn(|v| v as u8);
+    let total = pixels.len() as u32;
+    if total == 0 {
+        return identity;
+    }
+    let hist = histogram(pixels);
+    let clip = total * STRETCH_CLIP_PERCENT / 100;
+    let low = u32::from(nth_value(&hist, clip));
+    let high = u32::from(nth_value(&hist, total - 1 - clip));
+    if high <= low {
+        return identity;
+    }
+    std::array::from_fn(|v| {
+        let v = (v as u32).clamp(low, high);
+        ((v - low) * 255 / (high - low)) as u8
+    })
+}
+
+/// Square-window median using a sliding histogram per row
+fn median_filter(map: &DepthMap, radius: usize) -> Vec<u8> {
+    let width = map.width as usize;
+    let height = map.height as usize;
+    let src = &map.pixels;
+    let mut out = vec![0u8; src.len()];
+    if width == 0 || height == 0 {
+        return out;
+    }
+
+    for y in 0..height {
+        let top = y.saturating_sub(radius);
+        let bottom = (y + radius).min(height - 1);
+        let column_len = (bottom - top + 1) as u32;
+        let column = |x: usize| (top..=bottom).map(move |row| usize::from(src[row * width + x]));
+
+        let mut hist = [0u32; 256];
+        let mut count = 0u32;
+        for x in 0..=radius.min(width - 1) {
+            column(x).for_each(|bin| hist[bin] += 1);
+            count += column_len;
+        }
+
+        for x in 0..width {
+            out[y * width + x] = nth_value(&hist, count / 2);
+            // Slide: drop column x - radius, take column x + radius + 1
+            if x >= radius {
+                column(x - radius).for_each(|bin| hist[bin] -= 1);
+                count -= column_len;
+            }
+            if x + radius + 1 < width {
+                column(x + radius + 1).for_each(|bin| hist[bin] += 1);
+                count += column_len;
+            }
+        }
+    }
+    out
+}
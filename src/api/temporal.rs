@@ -0,0 +1,232 @@
+use std::collections::VecDeque;
+
+use super::depth::{DepthFormat, DepthMap};
+
+/// Upper bound on the smoothing window a client may request
+pub const MAX_SMOOTHING_FRAMES: usize = 16;
+
+/// Per-connection blend of recent depth frames to damp flicker
+#[derive(Debug)]
+pub struct TemporalSmoother {
+    /// Newest first
+    frames: VecDeque<Vec<f32>>,
+    capacity: usize,
+    /// EMA weights by frame age, newest first, renormalized over the frames held
+    weights: Vec<f32>,
+    dimensions: (u32, u32),
+}
+
+impl TemporalSmoother {
+    /// 0 or 1 frames builds a pass-through; callers bound `frames` to MAX_SMOOTHING_FRAMES
+    pub fn new(frames: usize) -> Self {
+        assert!(
+            frames <= MAX_SMOOTHING_FRAMES,
+            "Smoothing window {} exceeds {}",
+            frames,
+            MAX_SMOOTHING_FRAMES
+        );
+        // No history to blend, keep no weights so blend is never reachable
+        let frames = if frames > 1 { frames } else { 0 };
+        // Standard EMA span: alpha = 2 / (N + 1)
+        let alpha = 2.0 / (frames as f32 + 1.0);
+        let weights = (0..frames)
+            .map(|age| alpha * (1.0 - alpha).powi(age as i32))
+            .collect();
+        Self {
+            frames: VecDeque::with_capacity(frames),
+            capacity: frames,
+            weights,
+            dimensions: (0, 0),
+        }
+    }
+
+    /// One frame or fewer is a pass-through
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 1
+    }
+
+    /// Blend a /ws/depth payload with recent frames, re-encoded in the same format
+    pub fn smooth(&mut self, format: DepthFormat, payload: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        if !self.is_enabled() {
+            return Ok(payload);
+        }
+        match format {
+            DepthFormat::U8 => {
+                let DepthMap {
+                    width,
+                    height,
+                    pixels,
+                } = DepthMap::from_bytes(&payload)?;
+                let samples = pixels.into_iter().map(f32::from).collect();
+                let pixels = self
+                    .blend(width, height, samples)
+                    .into_iter()
+                    .map(|v| v.round() as u8)
+                    .collect();
+                Ok(DepthMap {
+                    width,
+                    height,
+                    pixels,
+                }
+                .to_bytes())
+            }
+            DepthFormat::U16 => {
+                let DepthMap {
+                    width,
+                    height,
+                    pixels,
+                } = DepthMap::<u16>::from_le_bytes(strip_tag(format, &payload)?)?;
+                let samples = pixels.into_iter().map(f32::from).collect();
+                let pixels = self
+                    .blend(width, height, samples)
+                    .into_iter()
+                    .map(|v| v.round() as u16)
+                    .collect();
+                let map = DepthMap {
+                    width,
+                    height,
+                    pixels,
+                };
+                Ok(tagged(format, map.to_le_bytes()))
+            }
+            DepthFormat::F32 => {
+                let DepthMap {
+                    width,
+                    height,
+                    pixels,
+                } = DepthMap::<f32>::from_le_bytes(strip_tag(format, &payload)?)?;
+                let map = DepthMap {
+                    width,
+                    height,
+                    pixels: self.blend(width, height, pixels),
+                };
+                Ok(tagged(format, map.to_le_bytes()))
+            }
+        }
+    }
+
+    /// Push the newest frame and return the weighted average over the buffer
+    fn blend(&mut self, width: u32, height: u32, samples: Vec<f32>) -> Vec<f32> {
+        // Resolution change invalidates history
+        if self.dimensions != (width, height) {
+            self.frames.clear();
+            self.dimensions = (width, height);
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_back();
+        }
+        self.frames.push_front(samples);
+
+        // Renormalize while the buffer is still filling
+        let total: f32 = self.weights[..self.frames.len()].iter().sum();
+        let mut blended = vec![0.0; self.frames[0].len()];
+        for (frame, weight) in self.frames.iter().zip(&self.weights) {
+            let weight = weight / total;
+            blended
+                .iter_mut()
+                .zip(frame)
+                .for_each(|(out, sample)| *out += sample * weight);
+        }
+        blended
+    }
+}
+
+fn strip_tag(format: DepthFormat, payload: &[u8]) -> anyhow::Result<&[u8]> {
+    match payload.split_first() {
+        Some((tag, rest)) if *tag == format.tag() => Ok(rest),
+        _ => Err(anyhow::anyhow!(
+            "Depth payload is not tagged as {:?}",
+            format
+        )),
+    }
+}
+
+fn tagged(format: DepthFormat, bytes: Vec<u8>) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(1 + bytes.len());
+    payload.push(format.tag());
+    payload.extend_from_slice(&bytes);
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn weights_follow_the_ema_span() {
+        // alpha = 2 / (3 + 1)
+        let smoother = TemporalSmoother::new(3);
+        assert_close(&smoother.weights, &[0.5, 0.25, 0.125]);
+    }
+
+    #[test]
+    fn blend_renormalizes_while_filling() {
+        let mut smoother = TemporalSmoother::new(3);
+        assert_close(&smoother.blend(1, 1, vec![0.0]), &[0.0]);
+        // (8 * 0.5 + 0 * 0.25) / 0.75
+        assert_close(&smoother.blend(1, 1, vec![8.0]), &[16.0 / 3.0]);
+        // (16 * 0.5 + 8 * 0.25 + 0 * 0.125) / 0.875
+        assert_close(&smoother.blend(1, 1, vec![16.0]), &[10.0 / 0.875]);
+        // Full buffer drops the oldest frame (0)
+        assert_close(&smoother.blend(1, 1, vec![24.0]), &[17.0 / 0.875]);
+    }
+
+    #[test]
+    fn resolution_change_resets_history() {
+        let mut smoother = TemporalSmoother::new(4);
+        smoother.blend(2, 1, vec![100.0, 100.0]);
+        smoother.blend(2, 1, vec![100.0, 100.0]);
+        assert_close(&smoother.blend(1, 2, vec![4.0, 8.0]), &[4.0, 8.0]);
+        assert_eq!(smoother.frames.len(), 1);
+    }
+
+    #[test]
+    fn smooth_keeps_the_u8_encoding() {
+        let mut smoother = TemporalSmoother::new(2);
+        let frame = |value| {
+            DepthMap {
+                width: 2,
+                height: 1,
+                pixels: vec![value; 2],
+            }
+            .to_bytes()
+        };
+        smoother.smooth(DepthFormat::U8, frame(0)).unwrap();
+        let smoothed = smoother.smooth(DepthFormat::U8, frame(80)).unwrap();
+        // alpha = 2/3: (80 * 2/3 + 0 * 2/9) / (8/9)
+        let DepthMap {
+            width,
+            height,
+            pixels,
+        } = DepthMap::from_bytes(&smoothed).unwrap();
+        assert_eq!((width, height), (2, 1));
+        assert_eq!(pixels, [60, 60]);
+    }
+
+    #[test]
+    fn short_windows_pass_through() {
+        for frames in [0, 1] {
+            let mut smoother = TemporalSmoother::new(frames);
+            assert!(!smoother.is_enabled());
+            assert!(smoother.weights.is_empty());
+            let payload = vec![1, 2, 3];
+            assert_eq!(
+                smoother.smooth(DepthFormat::U8, payload.clone()).unwrap(),
+                payload
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Smoothing window 17 exceeds 16")]
+    fn oversized_window_panics() {
+        TemporalSmoother::new(MAX_SMOOTHING_FRAMES + 1);
+    }
+}
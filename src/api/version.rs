@@ -0,0 +1,109 @@
+use axum::{Router, extract::State, response::Json, routing::get};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+
+use super::depth::{DepthModel, SharedDepthModel};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct VersionResponse {
+    version: &'static str,
+    git_commit: &'static str,
+    build_date: &'static str,
+    /// "onnx" or "pytorch", null until the model loads
+    depth_backend: Option<String>,
+    /// Device reported by the estimator, null until the model loads
+    depth_device: Option<String>,
+}
+
+/// Backend and device of the loaded model, if any
+type ModelInfo = (Option<String>, Option<String>);
+
+#[derive(Clone)]
+struct VersionState {
+    model: SharedDepthModel,
+    /// Last info read from the model, served while inference holds its lock
+    last_seen: Arc<Mutex<ModelInfo>>,
+}
+
+fn model_info(model: Option<&DepthModel>) -> ModelInfo {
+    (
+        model.map(|m| m.backend().to_string()),
+        model.map(|m| m.active_device().to_string()),
+    )
+}
+
+/// Define routes for this endpoint
+/// Path: /api/version
+pub fn routes(model: SharedDepthModel) -> Router {
+    let seen = match model.try_lock() {
+        Ok(guard) => model_info(guard.as_ref()),
+        Err(_) => (None, None),
+    };
+    Router::new()
+        .route("/api/version", get(handler))
+        .with_state(VersionState {
+            model,
+            last_seen: Arc::new(Mutex::new(seen)),
+        })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/version",
+    tag = "info",
+    responses((status = 200, description = "Build info and loaded depth backend", body = VersionResponse))
+)]
+async fn handler(State(state): State<VersionState>) -> Json<VersionResponse> {
+    let VersionState { model, last_seen } = state;
+    // Never queue behind inference for build info
+    let (depth_backend, depth_device) = match model.try_lock() {
+        Ok(guard) => {
+            let seen = model_info(guard.as_ref());
+            drop(guard);
+            *last_seen.lock().expect("version cache lock poisoned") = seen.clone();
+            seen
+        }
+        Err(_) => last_seen
+            .lock()
+            .expect("version cache lock poisoned")
+            .clone(),
+    };
+
+    Json(VersionResponse {
+        version: env!("CARGO_PKG_VERSION"),
+        git_commit: env!("VERGEN_GIT_SHA"),
+        build_date: env!("VERGEN_BUILD_DATE"),
+        depth_backend,
+        depth_device,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn busy_model_serves_last_seen_backend() {
+        let model = SharedDepthModel::default();
+        *model.lock().await = Some(DepthModel::stub());
+        let app = routes(model.clone());
+
+        // Inference holding the lock must not stall the endpoint
+        let _busy = model.lock().await;
+        let request = Request::get("/api/version").body(Body::empty()).unwrap();
+        let response = tokio::time::timeout(Duration::from_secs(1), app.oneshot(request))
+            .await
+            .expect("/api/version waited on the model lock")
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["depth_backend"], "onnx");
+        assert_eq!(body["depth_device"], "CPU");
+    }
+}
@@ -5,72 +5,400 @@ use axum::{
     },
     response::Response,
 };
-use futures_util::{SinkExt, StreamExt};
+use futures_util::{SinkExt, StreamExt, TryStreamExt, stream};
+use serde::Deserialize;
 use std::time::Instant;
-use tracing::{error, info};
+use tracing::{Instrument, Span, debug, error, info, trace};
+use uuid::Uuid;
 
-use super::depth::{SharedDepthModel, run_depth_inference};
+use super::depth::{DepthFormat, DepthInferenceQueue};
+use super::temporal::{MAX_SMOOTHING_FRAMES, TemporalSmoother};
+use crate::metrics::metrics;
+
+/// Leading byte of a batched binary message; JPEG, PNG and WebP never start with it
+const BATCH_MAGIC: u8 = 0xBD;
+
+/// Frames accepted in one batch message
+const MAX_BATCH_FRAMES: usize = 64;
+
+/// State for the /ws/depth route
+#[derive(Clone)]
+pub struct WsDepthState {
+    pub queue: DepthInferenceQueue,
+    /// zstd level used when a client negotiates compression
+    pub compression_level: i32,
+}
+
+/// Per-connection encoding of binary results
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Compression {
+    Zstd,
+}
+
+/// Optional first text frame configuring the connection
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SessionConfig {
+    format: DepthFormat,
+    /// Larger input frames are downscaled to fit before inference
+    max_input_resolution: Option<(u32, u32)>,
+    /// Recent frames blended to damp flicker, 0 or 1 disables, at most 16
+    temporal_smoothing_frames: usize,
+    /// Compress each binary result, raw when absent
+    compress: Option<Compression>,
+    /// Client-chosen correlation ID, replaces the generated one
+    session_id: Option<Uuid>,
+    /// Prefix each binary result with the 16 session ID bytes
+    session_prefix: bool,
+}
+
+impl SessionConfig {
+    /// Parse the handshake frame and bound client-chosen sizes
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        let config: Self = serde_json::from_str(text)?;
+        anyhow::ensure!(
+            config.temporal_smoothing_frames <= MAX_SMOOTHING_FRAMES,
+            "temporal_smoothing_frames must be at most {}, got {}",
+            MAX_SMOOTHING_FRAMES,
+            config.temporal_smoothing_frames
+        );
+        Ok(config)
+    }
+}
 
 /// WebSocket upgrade handler for /ws/depth
-pub async fn ws_depth_handler(
-    ws: WebSocketUpgrade,
-    State(model): State<SharedDepthModel>,
-) -> Response {
-    ws.on_upgrade(move |socket| handle_depth_socket(socket, model))
+#[utoipa::path(
+    get,
+    path = "/ws/depth",
+    tag = "depth",
+    description = "Upgrade to a WebSocket. Send JPEG, PNG or WebP frames as binary messages, receive grayscale depth buffers back. An optional first text frame configures the connection: {\"format\":\"u16\"|\"f32\"} switches results to a 1-byte format tag, the dimension header, then little-endian samples; {\"max_input_resolution\":[w,h]} downscales larger frames; {\"temporal_smoothing_frames\":N} (N <= 16) blends each result with the previous N-1 using EMA weights; {\"compress\":\"zstd\"} sends every result as a zstd frame; {\"session_id\":\"<uuid>\"} replaces the generated session ID used in server logs; {\"session_prefix\":true} prefixes every binary result with the 16 session ID bytes, outside any compression. Binary messages starting with 0xBD carry a batch: a u32 LE frame count, then each frame as a u32 LE length and payload; the reply uses the same framing.",
+    responses((status = 101, description = "Switching to the WebSocket protocol"))
+)]
+#[tracing::instrument(skip_all)]
+pub async fn ws_depth_handler(ws: WebSocketUpgrade, State(state): State<WsDepthState>) -> Response {
+    // The upgraded socket runs in its own task; keep it under the upgrade request's trace
+    let span = Span::current();
+    ws.on_upgrade(move |socket| handle_depth_socket(socket, state).instrument(span))
 }
 
 /// Handle the WebSocket connection
-async fn handle_depth_socket(socket: WebSocket, model: SharedDepthModel) {
+#[tracing::instrument(skip_all)]
+async fn handle_depth_socket(socket: WebSocket, state: WsDepthState) {
+    let WsDepthState {
+        queue,
+        compression_level,
+    } = state;
     let (mut sender, mut receiver) = socket.split();
 
-    info!("[WS-DEPTH] Client connected");
+    let mut session_id = Uuid::new_v4();
+    info!(component = "ws_depth", session = %session_id, "Client connected");
+    metrics().ws_depth_connections.inc();
+
+    let mut session = SessionConfig::default();
+    let mut smoother = TemporalSmoother::new(session.temporal_smoothing_frames);
+    let mut first_message = true;
 
     while let Some(msg) = receiver.next().await {
         let msg = match msg {
             Ok(m) => m,
             Err(e) => {
-                error!("[WS-DEPTH] Receive error: {}", e);
+                error!(component = "ws_depth", session = %session_id, error = %e, "Receive error");
                 break;
             }
         };
 
+        // Format handshake is only honoured before any frame
+        let handshake = std::mem::take(&mut first_message);
+
         match msg {
-            Message::Binary(jpeg_bytes) => {
+            Message::Text(text) if handshake && text.starts_with('{') => {
+                match SessionConfig::parse(&text) {
+                    Ok(requested) => {
+                        if let Some(requested_id) = requested.session_id {
+                            info!(
+                                component = "ws_depth",
+                                session = %session_id,
+                                client_session = %requested_id,
+                                "Client session ID"
+                            );
+                            session_id = requested_id;
+                        }
+                        info!(
+                            component = "ws_depth",
+                            session = %session_id,
+                            config = ?requested,
+                            "Session config"
+                        );
+                        smoother = TemporalSmoother::new(requested.temporal_smoothing_frames);
+                        session = requested;
+                    }
+                    Err(e) => {
+                        let _ = sender
+                            .send(Message::Text(format!("error: bad handshake: {}", e)))
+                            .await;
+                    }
+                }
+            }
+            Message::Binary(message) => {
                 let start = Instant::now();
+                let SessionConfig {
+                    format,
+                    max_input_resolution,
+                    temporal_smoothing_frames: _,
+                    compress,
+                    session_id: _,
+                    session_prefix,
+                } = session;
 
-                match run_depth_inference(&model, jpeg_bytes).await {
+                let batched = message.first() == Some(&BATCH_MAGIC);
+                let result = if batched {
+                    debug!(component = "ws_depth", session = %session_id, "Batch message");
+                    infer_batch(&queue, format, max_input_resolution, &message).await
+                } else {
+                    infer_frame(&queue, format, max_input_resolution, message)
+                        .await
+                        .map(|depth_bytes| vec![depth_bytes])
+                };
+                // Smooth in arrival order so batched frames blend like single ones
+                let result = result
+                    .and_then(|results| {
+                        results
+                            .into_iter()
+                            .map(|depth_bytes| smoother.smooth(format, depth_bytes))
+                            .collect::<anyhow::Result<Vec<_>>>()
+                    })
+                    .map(|mut results| {
+                        if batched {
+                            encode_batch(&results)
+                        } else {
+                            results.remove(0)
+                        }
+                    });
+                // zstd is CPU-bound, keep it off the async threads
+                let result = match (result, compress) {
+                    (Ok(depth_bytes), Some(Compression::Zstd)) => {
+                        tokio::task::spawn_blocking(move || {
+                            compress_zstd(session_id, &depth_bytes, compression_level)
+                        })
+                        .await
+                        .unwrap_or_else(|e| Err(e.into()))
+                    }
+                    (result, _) => result,
+                };
+                let result = result.map(|depth_bytes| {
+                    if session_prefix {
+                        [session_id.as_bytes().as_slice(), &depth_bytes].concat()
+                    } else {
+                        depth_bytes
+                    }
+                });
+                match result {
                     Ok(depth_bytes) => {
-                        let rtt = start.elapsed().as_millis();
-                        info!("[WS-DEPTH] Inference RTT: {}ms", rtt);
+                        let rtt_ms = start.elapsed().as_millis() as u64;
+                        info!(
+                            component = "ws_depth",
+                            session = %session_id,
+                            rtt_ms,
+                            "Inference RTT"
+                        );
 
                         if let Err(e) = sender.send(Message::Binary(depth_bytes)).await {
-                            error!("[WS-DEPTH] Send error: {}", e);
+                            error!(component = "ws_depth", session = %session_id, error = %e, "Send error");
                             break;
                         }
                     }
                     Err(e) => {
-                        error!("[WS-DEPTH] Inference error: {}", e);
+                        error!(component = "ws_depth", session = %session_id, error = %e, "Inference error");
                         // Send error message
                         let _ = sender.send(Message::Text(format!("error: {}", e))).await;
                     }
                 }
             }
-            Message::Text(text) => {
-                // Handle text messages (e.g., ping/config)
-                if text == "ping" {
-                    let _ = sender.send(Message::Text("pong".to_string())).await;
-                }
+            // Handle text messages (e.g., ping/config)
+            Message::Text(text) if text == "ping" => {
+                let _ = sender.send(Message::Text("pong".to_string())).await;
             }
             Message::Ping(data) => {
                 let _ = sender.send(Message::Pong(data)).await;
             }
             Message::Close(_) => {
-                info!("[WS-DEPTH] Client disconnected");
+                info!(component = "ws_depth", session = %session_id, "Client disconnected");
                 break;
             }
             _ => {}
         }
     }
 
-    info!("[WS-DEPTH] Connection closed");
+    metrics().ws_depth_connections.dec();
+    info!(component = "ws_depth", session = %session_id, "Connection closed");
+}
+
+/// Run one frame through the queue, converted to JPEG once admitted
+#[tracing::instrument(name = "ws_depth_frame", skip_all, fields(bytes = frame.len()))]
+async fn infer_frame(
+    queue: &DepthInferenceQueue,
+    format: DepthFormat,
+    max_input_resolution: Option<(u32, u32)>,
+    frame: Vec<u8>,
+) -> anyhow::Result<Vec<u8>> {
+    // Full queue fails fast with an error frame rather than stalling the socket
+    queue.try_infer(format, frame, max_input_resolution).await
+}
+
+/// Run a batch through the queue, results in input order
+#[tracing::instrument(name = "ws_depth_batch", skip_all, fields(bytes = message.len()))]
+async fn infer_batch(
+    queue: &DepthInferenceQueue,
+    format: DepthFormat,
+    max_input_resolution: Option<(u32, u32)>,
+    message: &[u8],
+) -> anyhow::Result<Vec<Vec<u8>>> {
+    let frames = parse_batch(message)?;
+    // Never ask for more slots than the queue has, so a large batch waits its turn
+    stream::iter(frames)
+        .map(|frame| infer_frame(queue, format, max_input_resolution, frame))
+        .buffered(queue.slots())
+        .try_collect()
+        .await
+}
+
+/// Split `0xBD, u32 LE count, (u32 LE length, payload) * count` into payloads
+fn parse_batch(message: &[u8]) -> anyhow::Result<Vec<Vec<u8>>> {
+    let mut rest = message
+        .strip_prefix(&[BATCH_MAGIC])
+        .ok_or_else(|| anyhow::anyhow!("Batch is missing the magic byte"))?;
+    let count = read_u32_le(&mut rest)? as usize;
+    anyhow::ensure!(
+        (1..=MAX_BATCH_FRAMES).contains(&count),
+        "Batch must hold 1-{} frames, got {}",
+        MAX_BATCH_FRAMES,
+        count
+    );
+    let mut frames = Vec::with_capacity(count);
+    for index in 0..count {
+        let len = read_u32_le(&mut rest)? as usize;
+        anyhow::ensure!(
+            rest.len() >= len,
+            "Batch frame {} claims {} bytes, {} left",
+            index,
+            len,
+            rest.len()
+        );
+        let (frame, tail) = rest.split_at(len);
+        frames.push(frame.to_vec());
+        rest = tail;
+    }
+    anyhow::ensure!(rest.is_empty(), "Batch has {} trailing bytes", rest.len());
+    Ok(frames)
+}
+
+fn read_u32_le(bytes: &mut &[u8]) -> anyhow::Result<u32> {
+    anyhow::ensure!(bytes.len() >= 4, "Batch truncated");
+    let (value, rest) = bytes.split_at(4);
+    *bytes = rest;
+    Ok(u32::from_le_bytes([value[0], value[1], value[2], value[3]]))
+}
+
+/// Inverse of `parse_batch`, one payload per result
+fn encode_batch(results: &[Vec<u8>]) -> Vec<u8> {
+    let payload_len: usize = results.iter().map(|r| 4 + r.len()).sum();
+    let mut message = Vec::with_capacity(5 + payload_len);
+    message.push(BATCH_MAGIC);
+    message.extend_from_slice(&(results.len() as u32).to_le_bytes());
+    for result in results {
+        message.extend_from_slice(&(result.len() as u32).to_le_bytes());
+        message.extend_from_slice(result);
+    }
+    message
+}
+
+/// Encode one result as a zstd frame and record the ratio
+fn compress_zstd(session_id: Uuid, depth_bytes: &[u8], level: i32) -> anyhow::Result<Vec<u8>> {
+    let compressed = zstd::encode_all(depth_bytes, level)?;
+    trace!(
+        component = "ws_depth",
+        session = %session_id,
+        raw_bytes = depth_bytes.len(),
+        compressed_bytes = compressed.len(),
+        "zstd compressed"
+    );
+    metrics()
+        .depth_compression_ratio
+        .observe(depth_bytes.len() as f64 / compressed.len().max(1) as f64);
+    Ok(compressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handshake_accepts_max_smoothing_window() {
+        let config = SessionConfig::parse(r#"{"temporal_smoothing_frames":16}"#).unwrap();
+        assert_eq!(config.temporal_smoothing_frames, MAX_SMOOTHING_FRAMES);
+    }
+
+    #[test]
+    fn handshake_rejects_oversized_smoothing_window() {
+        for frames in ["17", "18446744073709551615"] {
+            let text = format!(r#"{{"temporal_smoothing_frames":{}}}"#, frames);
+            assert!(SessionConfig::parse(&text).is_err(), "{} accepted", frames);
+        }
+    }
+
+    #[test]
+    fn batch_round_trips() {
+        let frames = vec![b"first".to_vec(), Vec::new(), vec![0xFF; 300]];
+        let message = encode_batch(&frames);
+        assert_eq!(message[..5], [BATCH_MAGIC, 3, 0, 0, 0]);
+        assert_eq!(message[5..9], 5u32.to_le_bytes());
+        assert_eq!(parse_batch(&message).unwrap(), frames);
+    }
+
+    #[test]
+    fn batch_rejects_truncated_length_prefix() {
+        let mut message = encode_batch(&[b"frame".to_vec()]);
+        message.truncate(7);
+        let error = parse_batch(&message).unwrap_err().to_string();
+        assert_eq!(error, "Batch truncated");
+    }
+
+    #[test]
+    fn batch_rejects_count_that_disagrees_with_payload() {
+        let mut message = encode_batch(&[b"a".to_vec(), b"b".to_vec()]);
+        // One frame more than sent: runs out of bytes
+        message[1] = 3;
+        assert!(parse_batch(&message).is_err());
+        // One frame fewer: the second frame is left over
+        message[1] = 1;
+        let error = parse_batch(&message).unwrap_err().to_string();
+        assert_eq!(error, "Batch has 5 trailing bytes");
+    }
+
+    #[test]
+    fn batch_rejects_oversized_frame_length() {
+        let mut message = encode_batch(&[b"frame".to_vec()]);
+        message[5] = 6;
+        let error = parse_batch(&message).unwrap_err().to_string();
+        assert_eq!(error, "Batch frame 0 claims 6 bytes, 5 left");
+    }
+
+    #[test]
+    fn batch_rejects_too_many_frames() {
+        let frames = vec![b"x".to_vec(); MAX_BATCH_FRAMES + 1];
+        assert!(parse_batch(&encode_batch(&frames[1..])).is_ok());
+        let error = parse_batch(&encode_batch(&frames)).unwrap_err().to_string();
+        assert_eq!(error, "Batch must hold 1-64 frames, got 65");
+    }
+
+    #[test]
+    fn batch_rejects_empty_and_unmarked_messages() {
+        let empty = encode_batch(&[]);
+        assert_eq!(empty, [BATCH_MAGIC, 0, 0, 0, 0]);
+        let error = parse_batch(&empty).unwrap_err().to_string();
+        assert_eq!(error, "Batch must hold 1-64 frames, got 0");
+        assert!(parse_batch(&[]).is_err());
+        assert!(parse_batch(&[0xFF, 0xD8, 0xFF]).is_err());
+    }
 }
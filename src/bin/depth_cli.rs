@@ -0,0 +1,117 @@
+use anyhow::Context;
+use clap::Parser;
+use depth_browser::api::colormap::Colormap;
+use depth_browser::api::depth::DepthMap;
+use futures_util::{SinkExt, StreamExt};
+use image::ImageFormat;
+use std::io::{Cursor, Read, Write};
+use std::path::PathBuf;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Send a JPEG to a running depth server and write the depth result to stdout
+#[derive(Debug, Parser)]
+#[command(name = "depth_cli")]
+struct Cli {
+    /// JPEG file to send (reads stdin when omitted)
+    input: Option<PathBuf>,
+
+    /// Base URL of the depth server
+    #[arg(long, default_value = "http://127.0.0.1:3030")]
+    server: String,
+
+    /// Use the /ws/depth WebSocket instead of POST /api/depth/estimate
+    #[arg(long)]
+    ws: bool,
+
+    /// Write a colour-mapped PNG instead of raw depth bytes
+    #[arg(long)]
+    colormap: Option<Colormap>,
+}
+
+fn read_input(input: Option<&PathBuf>) -> anyhow::Result<Vec<u8>> {
+    match input {
+        Some(path) => std::fs::read(path).with_context(|| format!("reading {:?}", path)),
+        None => {
+            let mut buf = Vec::new();
+            std::io::stdin().read_to_end(&mut buf)?;
+            Ok(buf)
+        }
+    }
+}
+
+async fn estimate_http(server: &str, jpeg: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    let url = format!("{}/api/depth/estimate", server.trim_end_matches('/'));
+    let response = reqwest::Client::new()
+        .post(&url)
+        .header(reqwest::header::CONTENT_TYPE, "image/jpeg")
+        .body(jpeg)
+        .send()
+        .await?;
+    anyhow::ensure!(
+        response.status().is_success(),
+        "{} returned {}",
+        url,
+        response.status()
+    );
+    Ok(response.bytes().await?.to_vec())
+}
+
+async fn estimate_ws(server: &str, jpeg: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    let base = server.trim_end_matches('/');
+    let url = match base.split_once("://") {
+        Some(("https", rest)) => format!("wss://{}/ws/depth", rest),
+        Some((_, rest)) => format!("ws://{}/ws/depth", rest),
+        None => format!("ws://{}/ws/depth", base),
+    };
+    let (mut socket, _) = tokio_tungstenite::connect_async(&url)
+        .await
+        .with_context(|| format!("connecting to {}", url))?;
+    socket.send(Message::Binary(jpeg.into())).await?;
+
+    while let Some(msg) = socket.next().await {
+        match msg? {
+            Message::Binary(depth) => {
+                let _ = socket.close(None).await;
+                return Ok(depth.to_vec());
+            }
+            Message::Text(text) => anyhow::bail!("server replied: {}", text),
+            Message::Close(_) => break,
+            Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => {}
+        }
+    }
+    anyhow::bail!("connection closed before a depth frame arrived")
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let Cli {
+        input,
+        server,
+        ws,
+        colormap,
+    } = Cli::parse();
+
+    let jpeg = read_input(input.as_ref())?;
+    anyhow::ensure!(!jpeg.is_empty(), "input is empty");
+
+    let depth_bytes = if ws {
+        estimate_ws(&server, jpeg).await?
+    } else {
+        estimate_http(&server, jpeg).await?
+    };
+    let depth = DepthMap::from_bytes(&depth_bytes)?;
+    eprintln!("depth {}x{}", depth.width, depth.height);
+
+    let output = match colormap {
+        Some(colormap) => {
+            let mut png = Cursor::new(Vec::new());
+            colormap
+                .apply(&depth)
+                .write_to(&mut png, ImageFormat::Png)?;
+            png.into_inner()
+        }
+        None => depth_bytes,
+    };
+    std::io::stdout().write_all(&output)?;
+    Ok(())
+}
@@ -1,56 +1,399 @@
-use axum::http::{HeaderName, Method, header};
+use clap::{Parser, Subcommand};
+use depth_browser::config::{LogFormat, ServerConfig};
+use depth_browser::server::access_log::{IpRedactor, log_requests};
 use depth_browser::server::build_router;
-use tower_http::cors::{AllowOrigin, CorsLayer};
-use tracing::info;
+use depth_browser::server::client_cert::ClientCertLayer;
+use depth_browser::server::hsts::{self, HstsPolicy};
+use depth_browser::server::https_redirect::redirect_router;
+use depth_browser::server::proxy_trust::ProxyTrustLayer;
+use depth_browser::server::tls;
+use depth_browser::server::trace_context::propagate_trace_context;
+#[cfg(unix)]
+use depth_browser::server::{daemon, unix_listener};
+use depth_browser::telemetry;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tracing::subscriber::NoSubscriber;
+use tracing::{error, info};
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    // Load .env.local first, then fall back to .env
+/// Commented template printed by `generate-config`
+const CONFIG_TEMPLATE: &str = include_str!("../../config.example.toml");
+
+/// DepthXR server
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// TOML config file (default: ./config.toml if present)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Bind address, overrides config and SERVER_HOST
+    #[arg(long, global = true)]
+    host: Option<String>,
+
+    /// Port, overrides config and SERVER_PORT
+    #[arg(long, global = true)]
+    port: Option<u16>,
+
+    /// Tracing filter, e.g. "debug" or "info,depth_browser=trace"
+    #[arg(long, global = true, env = "RUST_LOG", default_value = "info")]
+    log_level: String,
+
+    /// Skip loading the depth model
+    #[arg(long, global = true)]
+    no_depth: bool,
+
+    /// Name shown in the startup log
+    #[arg(long, global = true, env = "APP_NAME", default_value = "DepthXR")]
+    app_name: String,
+
+    /// Also listen on this port and redirect every request to HTTPS, overrides config and HTTP_REDIRECT_PORT
+    #[arg(long, global = true)]
+    http_redirect_port: Option<u16>,
+
+    /// Domain checked against the HSTS preload list, overrides config and HSTS_DOMAIN
+    #[arg(long, global = true)]
+    hsts_domain: Option<String>,
+
+    /// Fork into the background (Unix only; on Windows run as a service)
+    #[arg(long)]
+    daemonize: bool,
+
+    /// PID file written after --daemonize, removed on shutdown
+    #[arg(long, default_value = "/var/run/depth_browser.pid")]
+    pid_file: PathBuf,
+
+    /// Serve on sockets passed by systemd (LISTEN_FDS) instead of binding (Unix only)
+    #[arg(long)]
+    systemd_socket: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the server (default)
+    Serve,
+    /// Validate the config and exit
+    CheckConfig,
+    /// Print a commented config.toml template
+    GenerateConfig,
+}
+
+/// Command-line flags that override the config file and env vars
+#[derive(Clone)]
+struct Overrides {
+    host: Option<String>,
+    port: Option<u16>,
+    no_depth: bool,
+    http_redirect_port: Option<u16>,
+    hsts_domain: Option<String>,
+}
+
+/// Config file, then env vars, then command-line flags
+fn load_config(path: Option<&Path>, overrides: Overrides) -> anyhow::Result<ServerConfig> {
+    let Overrides {
+        host,
+        port,
+        no_depth,
+        http_redirect_port,
+        hsts_domain,
+    } = overrides;
+    let mut config = ServerConfig::load(path)?;
+    if let Some(host) = host {
+        config.host = host;
+    }
+    if let Some(port) = port {
+        config.port = port;
+    }
+    if no_depth {
+        config.depth.enabled = false;
+    }
+    if let Some(port) = http_redirect_port {
+        config.http_redirect_port = Some(port);
+    }
+    if let Some(domain) = hsts_domain {
+        config.hsts.domain = Some(domain);
+    }
+    Ok(config)
+}
+
+fn main() -> anyhow::Result<()> {
+    // Load .env.local first, then fall back to .env, before clap reads env defaults
     let _ = dotenvy::from_filename(".env.local");
     let _ = dotenvy::dotenv();
 
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()),
-        )
-        .init();
-
-    let cors = CorsLayer::new()
-        .allow_origin(AllowOrigin::mirror_request())
-        .allow_methods([
-            Method::GET,
-            Method::POST,
-            Method::PUT,
-            Method::DELETE,
-            Method::OPTIONS,
-        ])
-        .allow_headers([
-            header::CONTENT_TYPE,
-            header::AUTHORIZATION,
-            header::UPGRADE,
-            header::CONNECTION,
-            HeaderName::from_static("sec-websocket-key"),
-            HeaderName::from_static("sec-websocket-version"),
-            HeaderName::from_static("sec-websocket-protocol"),
-        ])
-        .allow_credentials(true);
-
-    let app = build_router().await.layer(cors);
-
-    let host = std::env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
-    let port = std::env::var("SERVER_PORT").unwrap_or_else(|_| "3030".to_string());
-    let addr = format!("{}:{}", host, port);
-
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
-
-    let app_name = std::env::var("APP_NAME").unwrap_or_else(|_| "DepthXR".to_string());
+    let Cli {
+        command,
+        config,
+        host,
+        port,
+        log_level,
+        no_depth,
+        app_name,
+        http_redirect_port,
+        hsts_domain,
+        daemonize,
+        pid_file,
+        systemd_socket,
+    } = Cli::parse();
+    let overrides = Overrides {
+        host,
+        port,
+        no_depth,
+        http_redirect_port,
+        hsts_domain,
+    };
+
+    match command.unwrap_or(Command::Serve) {
+        Command::Serve => {}
+        Command::GenerateConfig => {
+            print!("{}", CONFIG_TEMPLATE);
+            return Ok(());
+        }
+        Command::CheckConfig => {
+            let config = load_config(config.as_deref(), overrides)?;
+            match config.validate() {
+                Ok(()) => {
+                    println!("Config OK");
+                    return Ok(());
+                }
+                Err(errors) => {
+                    for e in &errors {
+                        eprintln!("{}", e);
+                    }
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    // Thread-local until the global subscriber can start exporter threads
+    // The config picks the log format, so peek at it silently; the real load below logs and reports errors
+    let log_format = tracing::subscriber::with_default(NoSubscriber::default(), || {
+        load_config(config.as_deref(), overrides.clone())
+    })
+    .map_or(LogFormat::default(), |config| config.log_format);
+    let startup_tracing =
+        tracing::subscriber::set_default(telemetry::console_subscriber(&log_level, log_format)?);
+
+    let config = load_config(config.as_deref(), overrides)?;
+    if let Err(errors) = config.validate() {
+        for e in &errors {
+            error!("[CONFIG] {}", e);
+        }
+        error!("[CONFIG] {} problem(s), exiting", errors.len());
+        std::process::exit(1);
+    }
+
+    // Fork before the runtime spawns worker threads; config errors above still reach the terminal
+    #[cfg(unix)]
+    let _pid_file = if daemonize {
+        Some(daemon::daemonize(&pid_file)?)
+    } else {
+        None
+    };
+    #[cfg(not(unix))]
+    anyhow::ensure!(
+        !daemonize,
+        "--daemonize is only supported on Unix, run as a Windows service instead ({:?})",
+        pid_file
+    );
+
+    // After the fork (exporter thread) and outside the runtime (blocking HTTP client)
+    drop(startup_tracing);
+    let tracer_provider = telemetry::init(&log_level, log_format)?;
+
+    let result = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(serve(
+            config,
+            app_name,
+            systemd_socket,
+            tracer_provider.is_some(),
+        ));
+
+    // Flush spans still queued in the batch exporter
+    if let Some(provider) = tracer_provider
+        && let Err(e) = provider.shutdown()
+    {
+        error!("[OTEL] Tracer shutdown failed: {}", e);
+    }
+    result
+}
+
+/// Bind each configured TCP address
+async fn bind_listeners(
+    config: &ServerConfig,
+) -> anyhow::Result<Vec<(String, tokio::net::TcpListener)>> {
+    let mut listeners = Vec::new();
+    for addr in config.tcp_addrs() {
+        let listener = tokio::net::TcpListener::bind(&addr)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to bind {}: {}", addr, e))?;
+        listeners.push((addr, listener));
+    }
+    Ok(listeners)
+}
+
+/// TCP sockets pre-opened by systemd socket activation
+#[cfg(unix)]
+fn systemd_listeners() -> anyhow::Result<Vec<(String, tokio::net::TcpListener)>> {
+    let mut fds = listenfd::ListenFd::from_env();
+    anyhow::ensure!(
+        fds.len() > 0,
+        "--systemd-socket given but systemd passed no sockets (LISTEN_FDS)"
+    );
+    let mut listeners = Vec::new();
+    for index in 0..fds.len() {
+        let listener = fds
+            .take_tcp_listener(index)?
+            .ok_or_else(|| anyhow::anyhow!("systemd socket {} is not a TCP listener", index))?;
+        listener.set_nonblocking(true)?;
+        let listener = tokio::net::TcpListener::from_std(listener)?;
+        listeners.push((listener.local_addr()?.to_string(), listener));
+    }
+    Ok(listeners)
+}
+
+#[cfg(not(unix))]
+fn systemd_listeners() -> anyhow::Result<Vec<(String, tokio::net::TcpListener)>> {
+    anyhow::bail!("--systemd-socket is only supported on Unix")
+}
+
+/// Bind every listener and serve until a shutdown signal
+async fn serve(
+    config: ServerConfig,
+    app_name: String,
+    systemd_socket: bool,
+    trace_export: bool,
+) -> anyhow::Result<()> {
+    let redactor = Arc::new(IpRedactor::new(config.log_ip_redaction)?);
+
+    let app = build_router(&config)
+        .await?
+        .layer(axum::middleware::from_fn_with_state(redactor, log_requests));
+    // Continue the caller's trace from its traceparent header
+    let app = if trace_export {
+        app.layer(axum::middleware::from_fn(propagate_trace_context))
+    } else {
+        app
+    };
+    let app = app
+        // Reuse an incoming X-Request-Id or mint a UUID, echoed on the response
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
+        .layer(ProxyTrustLayer::new(config.trusted_proxies.clone()));
+
+    let listeners = if systemd_socket {
+        systemd_listeners()?
+    } else {
+        bind_listeners(&config).await?
+    };
+
+    let acceptor = config.tls.as_ref().map(tls::acceptor).transpose()?;
+    let hsts_policy = HstsPolicy::from_config(&config.hsts);
+    // Client certs only exist on TLS connections, so the unix socket is left alone
+    let tls_app = if config
+        .tls
+        .as_ref()
+        .is_some_and(|tls| tls.require_client_cert)
+    {
+        app.clone().layer(ClientCertLayer)
+    } else {
+        app.clone()
+    };
+    // Browsers only honour Strict-Transport-Security received over HTTPS
+    let tls_app = tls_app.layer(hsts_policy.layer()?);
+    let scheme = match acceptor {
+        Some(_) => "https",
+        None => "http",
+    };
 
     info!("Starting {}", app_name);
-    info!("Listening on http://{}", addr);
+    for (addr, _) in &listeners {
+        info!("Listening on {}://{}", scheme, addr);
+    }
+
+    // No-op unless started by systemd with Type=notify
+    #[cfg(unix)]
+    if let Err(e) = sd_notify::notify(false, &[sd_notify::NotifyState::Ready]) {
+        error!("[SYSTEMD] READY=1 notification failed: {}", e);
+    }
+
+    if hsts_policy.preload {
+        tokio::spawn(hsts::check_preload(hsts_policy, config.hsts.domain.clone()));
+    }
+
+    // Optional plain-HTTP listener that only redirects to HTTPS
+    if let Some(redirect_port) = config.http_redirect_port {
+        let https_port = config.port;
+        let redirect_addr = format!("{}:{}", config.host, redirect_port);
+        let redirect_listener = tokio::net::TcpListener::bind(&redirect_addr).await?;
+        info!(
+            "Redirecting http://{} to HTTPS port {}",
+            redirect_addr, https_port
+        );
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(redirect_listener, redirect_router(https_port, hsts_policy))
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+            {
+                error!("HTTPS redirect server error: {}", e);
+            }
+        });
+    }
+
+    // Same-host clients can skip TCP; no ConnectInfo, so no client IP in access logs
+    #[cfg(unix)]
+    let unix_server = config.listen_unix.clone().map(|path| {
+        let unix_listener = unix_listener::bind(&path)?;
+        info!("Listening on unix:{}", path.display());
+        anyhow::Ok(tokio::spawn(unix_listener::serve(
+            unix_listener,
+            path,
+            app.clone(),
+            shutdown_signal(),
+        )))
+    });
+    #[cfg(unix)]
+    let unix_server = unix_server.transpose()?;
+    #[cfg(not(unix))]
+    anyhow::ensure!(
+        config.listen_unix.is_none(),
+        "listen_unix is only supported on Unix"
+    );
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    // One task per address, all sharing the same router
+    let tcp_servers = listeners.into_iter().map(|(_, listener)| {
+        let app = app.clone();
+        let tls_app = tls_app.clone();
+        let acceptor = acceptor.clone();
+        tokio::spawn(async move {
+            match acceptor {
+                Some(acceptor) => tls::serve(listener, acceptor, tls_app, shutdown_signal()).await,
+                None => axum::serve(
+                    listener,
+                    app.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+                .map_err(Into::into),
+            }
+        })
+    });
+    for result in futures_util::future::try_join_all(tcp_servers).await? {
+        result?;
+    }
+
+    #[cfg(unix)]
+    if let Some(unix_server) = unix_server {
+        unix_server.await?;
+    }
 
     info!("Server shutdown complete");
 
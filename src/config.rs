@@ -0,0 +1,905 @@
+use ipnetwork::IpNetwork;
+use jsonwebtoken::Algorithm;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::info;
+
+use crate::server::hsts::PRELOAD_MIN_MAX_AGE;
+use crate::server::routing_rules::RoutingRules;
+
+/// Config file picked up from the working directory when --config is not given
+pub const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+/// Server-wide settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    /// TCP "host:port" addresses to bind; empty binds host:port
+    pub listen_addrs: Vec<String>,
+    /// Also serve on this Unix domain socket
+    pub listen_unix: Option<PathBuf>,
+    /// Terminate TLS on every TCP listener when set
+    pub tls: Option<TlsConfig>,
+    pub cors: CorsPolicyMap,
+    /// Reverse proxies whose X-Forwarded-For entries are trusted
+    pub trusted_proxies: Vec<IpNetwork>,
+    pub depth: DepthConfig,
+    /// zstd level (1-22) for /ws/depth clients that negotiate compression
+    pub depth_compression_level: i32,
+    /// Require a JWT on /ws/depth and /api/depth/preview when set
+    pub jwt: Option<JwtConfig>,
+    /// Console log line format
+    pub log_format: LogFormat,
+    /// /readyz reports 503 once RSS exceeds this, so orchestrators restart the process
+    pub memory_limit_mb: Option<u64>,
+    /// Also listen on this port and redirect every request to HTTPS
+    pub http_redirect_port: Option<u16>,
+    pub hsts: HstsConfig,
+    /// How client IPs are written to access logs
+    pub log_ip_redaction: IpRedaction,
+    /// JSON rules evaluated before the Next.js proxy fallback
+    pub routing_rules_file: Option<PathBuf>,
+    /// Rewrites applied to proxied Next.js HTML, in order
+    pub proxy_transforms: Vec<ProxyTransform>,
+}
+
+/// PEM files for in-process TLS termination
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// Certificate chain, leaf first
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// Verify client certificates against this CA when presented
+    pub client_ca: Option<PathBuf>,
+    /// Reject requests without a client certificate with 401, needs `client_ca`
+    #[serde(default)]
+    pub require_client_cert: bool,
+}
+
+/// CORS policies, keyed by route path
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsPolicyMap {
+    pub default: CorsPolicy,
+    /// Route path (e.g. "/ws/depth") to policy
+    pub overrides: HashMap<String, CorsPolicy>,
+}
+
+impl CorsPolicyMap {
+    /// Policy for a route, falling back to the default
+    pub fn policy_for(&self, path: &str) -> &CorsPolicy {
+        self.overrides.get(path).unwrap_or(&self.default)
+    }
+}
+
+/// Allowed origins, methods and headers for one route
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsPolicy {
+    /// Empty mirrors the request origin
+    pub origins: Vec<String>,
+    pub methods: Vec<String>,
+    pub headers: Vec<String>,
+}
+
+impl Default for CorsPolicy {
+    fn default() -> Self {
+        Self {
+            origins: Vec::new(),
+            methods: ["GET", "POST", "PUT", "DELETE", "OPTIONS"]
+                .map(str::to_string)
+                .to_vec(),
+            headers: [
+                "content-type",
+                "authorization",
+                "upgrade",
+                "connection",
+                "sec-websocket-key",
+                "sec-websocket-version",
+                "sec-websocket-protocol",
+            ]
+            .map(str::to_string)
+            .to_vec(),
+        }
+    }
+}
+
+/// JWT validation settings
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JwtConfig {
+    /// HMAC secret, or PEM public key for asymmetric algorithms
+    pub secret: String,
+    #[serde(default = "default_jwt_algorithm")]
+    pub algorithm: Algorithm,
+}
+
+fn default_jwt_algorithm() -> Algorithm {
+    Algorithm::HS256
+}
+
+// Keep the secret out of logs
+impl fmt::Debug for JwtConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtConfig")
+            .field("secret", &"<redacted>")
+            .field("algorithm", &self.algorithm)
+            .finish()
+    }
+}
+
+/// Depth inference settings
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DepthConfig {
+    /// Load the depth model; off serves everything else with depth routes unavailable
+    pub enabled: bool,
+    /// Keep a second model loaded for zero-downtime swaps
+    pub warm_standby: bool,
+    /// Frames allowed to wait for the inference worker, at least max_concurrent
+    pub queue_depth: usize,
+    /// Results kept by exact frame hash, 0 disables
+    pub cache_size: usize,
+    /// Compute device passed to the Python estimator
+    pub device: DepthDevice,
+    /// Frames in flight (queued or running) across all clients
+    pub max_concurrent: usize,
+    /// How long a frame may wait for a slot before a 429
+    pub queue_timeout_ms: u64,
+    /// Rust-side clean-up of u8 depth maps
+    pub post_process: PostProcessConfig,
+}
+
+/// Rust-side clean-up applied to u8 depth maps
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PostProcessConfig {
+    /// Stretch the 1st-99th percentile range to 0-255
+    pub contrast_stretch: bool,
+    /// Median filter window radius in pixels
+    pub median_filter_radius: Option<u8>,
+    /// Near becomes dark instead of bright
+    pub invert: bool,
+}
+
+/// Strict-Transport-Security header sent on TLS responses
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HstsConfig {
+    /// Seconds browsers remember to use HTTPS
+    pub max_age: u64,
+    pub include_subdomains: bool,
+    /// Add the preload directive and check eligibility at startup
+    pub preload: bool,
+    /// Domain looked up on the hstspreload.org list
+    pub domain: Option<String>,
+}
+
+impl Default for HstsConfig {
+    fn default() -> Self {
+        Self {
+            max_age: PRELOAD_MIN_MAX_AGE,
+            include_subdomains: true,
+            preload: false,
+            domain: None,
+        }
+    }
+}
+
+/// HTML rewrite for proxied Next.js responses
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyTransform {
+    /// Insert markup before `</body>`
+    InjectScript { html: String },
+    /// Replace every occurrence of `from` with `to`
+    ReplaceText { from: String, to: String },
+    /// Tag `<script>` elements with a fresh nonce and allow it in the CSP
+    AddNonce,
+}
+
+/// How client IPs are written to access logs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum IpRedaction {
+    /// Log the full address
+    #[default]
+    None,
+    /// Zero the last IPv4 octet / last 80 bits of IPv6
+    Partial,
+    /// Replace with an HMAC pseudonym that rotates daily and on restart
+    Full,
+}
+
+impl FromStr for IpRedaction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "none" | "" => Ok(Self::None),
+            "partial" => Ok(Self::Partial),
+            "full" => Ok(Self::Full),
+            _ => Err(anyhow::anyhow!(
+                "Unknown IP redaction '{}' (expected none, partial or full)",
+                s
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for IpRedaction {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Console log output: human-readable text or one JSON object per line
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow::anyhow!(
+                "Unknown log format '{}' (expected text or json)",
+                s
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for LogFormat {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+/// Compute device for the Python estimator ("auto", "cpu", "cuda", "cuda:N", "mps")
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum DepthDevice {
+    /// Let Python pick the best available device
+    #[default]
+    Auto,
+    Cpu,
+    Cuda(u8),
+    Mps,
+}
+
+impl FromStr for DepthDevice {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "cpu" => Ok(Self::Cpu),
+            "cuda" => Ok(Self::Cuda(0)),
+            "mps" => Ok(Self::Mps),
+            other => match other.strip_prefix("cuda:").map(str::parse) {
+                Some(Ok(index)) => Ok(Self::Cuda(index)),
+                _ => Err(anyhow::anyhow!(
+                    "Unknown depth device '{}' (expected auto, cpu, cuda[:N] or mps)",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for DepthDevice {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for DepthDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Cpu => write!(f, "cpu"),
+            Self::Cuda(index) => write!(f, "cuda:{}", index),
+            Self::Mps => write!(f, "mps"),
+        }
+    }
+}
+
+impl Default for DepthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            warm_standby: false,
+            queue_depth: 4,
+            cache_size: 16,
+            device: DepthDevice::Auto,
+            max_concurrent: 4,
+            queue_timeout_ms: 1000,
+            post_process: PostProcessConfig::default(),
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 3030,
+            listen_addrs: Vec::new(),
+            listen_unix: None,
+            tls: None,
+            cors: CorsPolicyMap::default(),
+            trusted_proxies: Vec::new(),
+            depth: DepthConfig::default(),
+            depth_compression_level: 3,
+            jwt: None,
+            log_format: LogFormat::Text,
+            memory_limit_mb: None,
+            http_redirect_port: None,
+            hsts: HstsConfig::default(),
+            log_ip_redaction: IpRedaction::None,
+            routing_rules_file: None,
+            proxy_transforms: Vec::new(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Check for contradictions and missing files, reporting every problem at once
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let Self {
+            host: _,
+            port,
+            listen_addrs,
+            listen_unix: _,
+            tls,
+            cors,
+            trusted_proxies: _,
+            depth,
+            depth_compression_level,
+            jwt,
+            log_format: _,
+            memory_limit_mb: _,
+            http_redirect_port,
+            hsts,
+            log_ip_redaction: _,
+            routing_rules_file,
+            proxy_transforms: _,
+        } = self;
+        let mut errors = Vec::new();
+
+        for addr in listen_addrs {
+            let port = addr.rsplit_once(':').map(|(_, port)| port.parse::<u16>());
+            if !matches!(port, Some(Ok(_))) {
+                errors.push(ConfigError::ListenAddr(addr.clone()));
+            }
+        }
+
+        if let Some(TlsConfig {
+            cert_path,
+            key_path,
+            client_ca,
+            require_client_cert,
+        }) = tls
+        {
+            let files = [
+                ("tls.cert_path", Some(cert_path)),
+                ("tls.key_path", Some(key_path)),
+            ];
+            let ca = [("tls.client_ca", client_ca.as_ref())];
+            for (field, path) in files.into_iter().chain(ca) {
+                if let Some(path) = path.filter(|path| !path.is_file()) {
+                    errors.push(ConfigError::MissingFile {
+                        field,
+                        path: path.clone(),
+                    });
+                }
+            }
+            if *require_client_cert && client_ca.is_none() {
+                errors.push(ConfigError::ClientCertWithoutCa);
+            }
+        }
+
+        for path in cors.overrides.keys().filter(|path| !path.starts_with('/')) {
+            errors.push(ConfigError::CorsPath(path.clone()));
+        }
+
+        // Zero would deadlock the inference queue rather than disable it
+        let sizes = [
+            ("depth.queue_depth", depth.queue_depth),
+            ("depth.max_concurrent", depth.max_concurrent),
+        ];
+        for (field, _) in sizes.into_iter().filter(|(_, size)| *size == 0) {
+            errors.push(ConfigError::Zero(field));
+        }
+        // Every admitted frame must fit the channel, else try_infer reports a full queue
+        if depth.max_concurrent > depth.queue_depth {
+            errors.push(ConfigError::ConcurrencyOverQueue {
+                max_concurrent: depth.max_concurrent,
+                queue_depth: depth.queue_depth,
+            });
+        }
+
+        if !(1..=22).contains(depth_compression_level) {
+            errors.push(ConfigError::CompressionLevel(*depth_compression_level));
+        }
+
+        if jwt.as_ref().is_some_and(|jwt| jwt.secret.is_empty()) {
+            errors.push(ConfigError::EmptyJwtSecret);
+        }
+
+        if *http_redirect_port == Some(*port) {
+            errors.push(ConfigError::RedirectPort(*port));
+        }
+        // Nothing would answer on the HTTPS side of the redirect
+        if http_redirect_port.is_some() && tls.is_none() {
+            errors.push(ConfigError::RedirectWithoutTls);
+        }
+
+        if let Some(domain) = &hsts.domain
+            && (domain.is_empty()
+                || domain.contains(['/', ':'])
+                || domain.contains(char::is_whitespace))
+        {
+            errors.push(ConfigError::HstsDomain(domain.clone()));
+        }
+
+        if let Some(path) = routing_rules_file
+            && let Err(e) = RoutingRules::from_file(path)
+        {
+            errors.push(ConfigError::RoutingRules(e.to_string()));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// TCP addresses to bind, falling back to host:port
+    pub fn tcp_addrs(&self) -> Vec<String> {
+        if self.listen_addrs.is_empty() {
+            vec![format!("{}:{}", self.host, self.port)]
+        } else {
+            self.listen_addrs.clone()
+        }
+    }
+
+    /// Load from `path`, else ./config.toml if present, else defaults; env vars override
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let default_path = Path::new(DEFAULT_CONFIG_FILE);
+        let config = match path {
+            Some(path) => Self::from_file(path)?,
+            None if default_path.exists() => Self::from_file(default_path)?,
+            None => Self::default(),
+        };
+        config.with_env()
+    }
+
+    /// Parse a TOML config file
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read config {:?}: {}", path, e))?;
+        let config: Self = toml::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid config {:?}: {}", path, e))?;
+        info!("[CONFIG] Loaded {:?}", path);
+        Ok(config)
+    }
+
+    /// Build from environment variables over defaults
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::default().with_env()
+    }
+
+    /// Apply environment variable overrides
+    fn with_env(self) -> anyhow::Result<Self> {
+        let Self {
+            mut host,
+            mut port,
+            mut listen_addrs,
+            mut listen_unix,
+            mut tls,
+            mut cors,
+            mut trusted_proxies,
+            mut depth,
+            mut depth_compression_level,
+            mut jwt,
+            mut log_format,
+            mut memory_limit_mb,
+            mut http_redirect_port,
+            mut hsts,
+            mut log_ip_redaction,
+            mut routing_rules_file,
+            mut proxy_transforms,
+        } = self;
+
+        if let Ok(value) = std::env::var("SERVER_HOST") {
+            host = value;
+        }
+        if let Ok(value) = std::env::var("SERVER_PORT") {
+            port = value
+                .parse()
+                .map_err(|e| anyhow::anyhow!("Invalid SERVER_PORT '{}': {}", value, e))?;
+        }
+        if let Ok(value) = std::env::var("SERVER_LISTEN_ADDRS") {
+            listen_addrs = split_list(&value).map(str::to_string).collect();
+        }
+        if let Ok(value) = std::env::var("SERVER_LISTEN_UNIX") {
+            listen_unix = Some(PathBuf::from(value));
+        }
+        if let (Ok(cert_path), Ok(key_path)) = (
+            std::env::var("TLS_CERT_PATH"),
+            std::env::var("TLS_KEY_PATH"),
+        ) {
+            let client_ca = std::env::var("TLS_CLIENT_CA")
+                .ok()
+                .map(PathBuf::from)
+                .or_else(|| tls.as_ref().and_then(|tls| tls.client_ca.clone()));
+            let require_client_cert = match std::env::var("TLS_REQUIRE_CLIENT_CERT") {
+                Ok(value) => value == "1",
+                Err(_) => tls.as_ref().is_some_and(|tls| tls.require_client_cert),
+            };
+            tls = Some(TlsConfig {
+                cert_path: PathBuf::from(cert_path),
+                key_path: PathBuf::from(key_path),
+                client_ca,
+                require_client_cert,
+            });
+        }
+        if let Ok(value) = std::env::var("CORS_ORIGINS") {
+            cors.default.origins = split_list(&value).map(str::to_string).collect();
+        }
+        if let Ok(value) = std::env::var("TRUSTED_PROXIES") {
+            trusted_proxies = parse_networks(&value)?;
+        }
+        if let Ok(value) = std::env::var("DEPTH_WARM_STANDBY") {
+            depth.warm_standby = value == "1";
+        }
+        if let Ok(value) = std::env::var("DEPTH_QUEUE_DEPTH") {
+            depth.queue_depth = value
+                .parse()
+                .map_err(|e| anyhow::anyhow!("Invalid DEPTH_QUEUE_DEPTH '{}': {}", value, e))?;
+        }
+        if let Ok(value) = std::env::var("DEPTH_DEVICE") {
+            depth.device = value.parse()?;
+        }
+        if let Ok(value) = std::env::var("DEPTH_MAX_CONCURRENT") {
+            depth.max_concurrent = value
+                .parse()
+                .map_err(|e| anyhow::anyhow!("Invalid DEPTH_MAX_CONCURRENT '{}': {}", value, e))?;
+        }
+        if let Ok(value) = std::env::var("DEPTH_QUEUE_TIMEOUT_MS") {
+            depth.queue_timeout_ms = value.parse().map_err(|e| {
+                anyhow::anyhow!("Invalid DEPTH_QUEUE_TIMEOUT_MS '{}': {}", value, e)
+            })?;
+        }
+        if let Ok(value) = std::env::var("DEPTH_CACHE_SIZE") {
+            depth.cache_size = value
+                .parse()
+                .map_err(|e| anyhow::anyhow!("Invalid DEPTH_CACHE_SIZE '{}': {}", value, e))?;
+        }
+        if let Ok(value) = std::env::var("DEPTH_COMPRESSION_LEVEL") {
+            depth_compression_level = value.parse().map_err(|e| {
+                anyhow::anyhow!("Invalid DEPTH_COMPRESSION_LEVEL '{}': {}", value, e)
+            })?;
+        }
+        if let Ok(secret) = std::env::var("JWT_SECRET") {
+            let algorithm = jwt
+                .as_ref()
+                .map_or_else(default_jwt_algorithm, |jwt| jwt.algorithm);
+            jwt = Some(JwtConfig { secret, algorithm });
+        }
+        if let Ok(value) = std::env::var("LOG_FORMAT") {
+            log_format = value.parse()?;
+        }
+        if let Ok(value) = std::env::var("MEMORY_LIMIT_MB") {
+            memory_limit_mb = Some(
+                value
+                    .parse()
+                    .map_err(|e| anyhow::anyhow!("Invalid MEMORY_LIMIT_MB '{}': {}", value, e))?,
+            );
+        }
+        if let Ok(value) = std::env::var("HTTP_REDIRECT_PORT") {
+            http_redirect_port =
+                Some(value.parse().map_err(|e| {
+                    anyhow::anyhow!("Invalid HTTP_REDIRECT_PORT '{}': {}", value, e)
+                })?);
+        }
+        if let Ok(value) = std::env::var("HSTS_MAX_AGE") {
+            hsts.max_age = value
+                .parse()
+                .map_err(|e| anyhow::anyhow!("Invalid HSTS_MAX_AGE '{}': {}", value, e))?;
+        }
+        if let Ok(value) = std::env::var("HSTS_INCLUDE_SUBDOMAINS") {
+            hsts.include_subdomains = value == "1";
+        }
+        if let Ok(value) = std::env::var("HSTS_PRELOAD") {
+            hsts.preload = value == "1";
+        }
+        if let Ok(value) = std::env::var("HSTS_DOMAIN") {
+            hsts.domain = Some(value);
+        }
+        if let Ok(value) = std::env::var("LOG_IP_REDACTION") {
+            log_ip_redaction = value.parse()?;
+        }
+        if let Ok(value) = std::env::var("ROUTING_RULES_FILE") {
+            routing_rules_file = Some(PathBuf::from(value));
+        }
+        // Inline JSON wins over the file
+        let transforms_json = match (
+            std::env::var("PROXY_TRANSFORMS"),
+            std::env::var("PROXY_TRANSFORMS_FILE"),
+        ) {
+            (Ok(inline), _) => Some(inline),
+            (Err(_), Ok(path)) => Some(std::fs::read_to_string(&path).map_err(|e| {
+                anyhow::anyhow!("Failed to read PROXY_TRANSFORMS_FILE {:?}: {}", path, e)
+            })?),
+            (Err(_), Err(_)) => None,
+        };
+        if let Some(json) = transforms_json {
+            proxy_transforms = serde_json::from_str(&json)
+                .map_err(|e| anyhow::anyhow!("Invalid PROXY_TRANSFORMS: {}", e))?;
+        }
+
+        if !trusted_proxies.is_empty() {
+            let listed: Vec<String> = trusted_proxies.iter().map(ToString::to_string).collect();
+            info!("[CONFIG] Trusted proxies: {}", listed.join(", "));
+        }
+
+        Ok(Self {
+            host,
+            port,
+            listen_addrs,
+            listen_unix,
+            tls,
+            cors,
+            trusted_proxies,
+            depth,
+            depth_compression_level,
+            jwt,
+            log_format,
+            memory_limit_mb,
+            http_redirect_port,
+            hsts,
+            log_ip_redaction,
+            routing_rules_file,
+            proxy_transforms,
+        })
+    }
+}
+
+/// One problem found by `ServerConfig::validate`
+#[derive(Debug)]
+pub enum ConfigError {
+    /// listen_addrs entry without a numeric port
+    ListenAddr(String),
+    /// Configured file does not exist
+    MissingFile {
+        field: &'static str,
+        path: PathBuf,
+    },
+    ClientCertWithoutCa,
+    /// CORS override key that can never match a route
+    CorsPath(String),
+    CompressionLevel(i32),
+    EmptyJwtSecret,
+    /// Size that must be at least 1
+    Zero(&'static str),
+    /// depth.max_concurrent admits more frames than the queue holds
+    ConcurrencyOverQueue {
+        max_concurrent: usize,
+        queue_depth: usize,
+    },
+    /// http_redirect_port equal to the main port
+    RedirectPort(u16),
+    RedirectWithoutTls,
+    /// hsts.domain that is not a bare host name
+    HstsDomain(String),
+    /// routing_rules_file missing or unparseable
+    RoutingRules(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ListenAddr(addr) => {
+                write!(f, "listen_addrs: '{}' is not host:port", addr)
+            }
+            Self::MissingFile { field, path } => {
+                write!(f, "{}: {:?} does not exist", field, path)
+            }
+            Self::ClientCertWithoutCa => {
+                write!(f, "tls.require_client_cert needs tls.client_ca")
+            }
+            Self::CorsPath(path) => {
+                write!(f, "cors.overrides: '{}' must start with '/'", path)
+            }
+            Self::CompressionLevel(level) => {
+                write!(f, "depth_compression_level must be 1-22, got {}", level)
+            }
+            Self::EmptyJwtSecret => write!(f, "jwt.secret is empty"),
+            Self::Zero(field) => write!(f, "{} must be at least 1", field),
+            Self::ConcurrencyOverQueue {
+                max_concurrent,
+                queue_depth,
+            } => write!(
+                f,
+                "depth.max_concurrent {} exceeds depth.queue_depth {}",
+                max_concurrent, queue_depth
+            ),
+            Self::RedirectPort(port) => {
+                write!(f, "http_redirect_port {} is also the main port", port)
+            }
+            Self::RedirectWithoutTls => write!(f, "http_redirect_port needs [tls]"),
+            Self::HstsDomain(domain) => {
+                write!(f, "hsts.domain: '{}' is not a bare host name", domain)
+            }
+            Self::RoutingRules(e) => write!(f, "routing_rules_file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Split a comma-separated list, skipping blanks
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+}
+
+/// Parse a comma-separated list of IPs or CIDRs
+fn parse_networks(value: &str) -> anyhow::Result<Vec<IpNetwork>> {
+    split_list(value)
+        .map(|entry| {
+            entry
+                .parse::<IpNetwork>()
+                .map_err(|e| anyhow::anyhow!("Invalid network '{}': {}", entry, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(config: ServerConfig) -> Vec<String> {
+        match config.validate() {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(errors(ServerConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn example_config_is_valid() {
+        let config: ServerConfig = toml::from_str(include_str!("../config.example.toml")).unwrap();
+        assert!(errors(config).is_empty());
+    }
+
+    #[test]
+    fn proxy_transforms_and_hsts_parse_from_toml() {
+        let config: ServerConfig = toml::from_str(
+            r#"
+            proxy_transforms = [
+              { inject_script = { html = "<script></script>" } },
+              { replace_text = { from = "a", to = "b" } },
+              "add_nonce",
+            ]
+            log_ip_redaction = "full"
+
+            [hsts]
+            preload = true
+            domain = "example.com"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.proxy_transforms.len(), 3);
+        assert_eq!(config.log_ip_redaction, IpRedaction::Full);
+        assert!(config.hsts.preload);
+        assert!(errors(config).is_empty());
+    }
+
+    #[test]
+    fn zero_queue_sizes_are_rejected() {
+        let mut config = ServerConfig::default();
+        config.depth.queue_depth = 0;
+        config.depth.max_concurrent = 0;
+        assert_eq!(
+            errors(config),
+            [
+                "depth.queue_depth must be at least 1",
+                "depth.max_concurrent must be at least 1"
+            ]
+        );
+    }
+
+    #[test]
+    fn concurrency_over_queue_depth_is_rejected() {
+        let mut config = ServerConfig::default();
+        config.depth.queue_depth = 2;
+        config.depth.max_concurrent = 3;
+        assert_eq!(
+            errors(config.clone()),
+            ["depth.max_concurrent 3 exceeds depth.queue_depth 2"]
+        );
+
+        config.depth.max_concurrent = 2;
+        assert!(errors(config).is_empty());
+    }
+
+    #[test]
+    fn moved_env_settings_are_validated() {
+        let config = ServerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 3030,
+            listen_addrs: Vec::new(),
+            listen_unix: None,
+            tls: None,
+            cors: CorsPolicyMap::default(),
+            trusted_proxies: Vec::new(),
+            depth: DepthConfig::default(),
+            depth_compression_level: 3,
+            jwt: None,
+            log_format: LogFormat::Text,
+            memory_limit_mb: None,
+            http_redirect_port: Some(3030),
+            hsts: HstsConfig {
+                max_age: PRELOAD_MIN_MAX_AGE,
+                include_subdomains: true,
+                preload: true,
+                domain: Some("https://example.com".to_string()),
+            },
+            log_ip_redaction: IpRedaction::None,
+            routing_rules_file: Some(PathBuf::from("/nonexistent/rules.json")),
+            proxy_transforms: Vec::new(),
+        };
+        let errors = errors(config);
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert_eq!(errors[0], "http_redirect_port 3030 is also the main port");
+        assert_eq!(errors[1], "http_redirect_port needs [tls]");
+        assert!(errors[2].starts_with("hsts.domain"));
+        assert!(errors[3].starts_with("routing_rules_file"));
+    }
+
+    #[test]
+    fn log_format_parse_is_lenient_about_case_and_spaces() {
+        assert_eq!(" JSON\n".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("text".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn unknown_ip_redaction_is_an_error() {
+        assert_eq!(
+            "Partial".parse::<IpRedaction>().unwrap(),
+            IpRedaction::Partial
+        );
+        assert!("hashed".parse::<IpRedaction>().is_err());
+    }
+}
@@ -1,2 +1,5 @@
 pub mod api;
+pub mod config;
+pub mod metrics;
 pub mod server;
+pub mod telemetry;
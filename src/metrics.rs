@@ -0,0 +1,183 @@
+use axum::{
+    Router,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use prometheus_client::encoding::text::encode;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::metrics::histogram::Histogram;
+use prometheus_client::registry::Registry;
+use std::sync::OnceLock;
+use tracing::error;
+
+use crate::api::memory::rss_bytes;
+
+#[cfg(feature = "metrics-runtime")]
+pub mod runtime;
+
+/// Inference latency buckets, 5ms to ~10s
+const INFERENCE_BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 7.5, 10.0,
+];
+
+/// GIL wait and hold buckets, 100us to ~10s
+const GIL_BUCKETS: [f64; 12] = [
+    0.0001, 0.0005, 0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 1.0, 2.5, 10.0,
+];
+
+/// Original / compressed size buckets
+const COMPRESSION_RATIO_BUCKETS: [f64; 9] = [1.0, 1.5, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0];
+
+/// Process-wide Prometheus metrics
+pub struct Metrics {
+    registry: Registry,
+    pub depth_inferences: Counter,
+    pub depth_cache_hits: Counter,
+    pub depth_inference_duration: Histogram,
+    pub gil_wait_duration: Histogram,
+    pub gil_held_duration: Histogram,
+    pub depth_model_loaded: Gauge,
+    pub depth_queue_depth: Gauge,
+    pub depth_compression_ratio: Histogram,
+    pub ws_depth_connections: Gauge,
+    pub memory_rss_bytes: Gauge,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let mut registry = Registry::default();
+
+        let depth_inferences = Counter::default();
+        registry.register(
+            "depth_inferences",
+            "Depth inferences run",
+            depth_inferences.clone(),
+        );
+
+        let depth_cache_hits = Counter::default();
+        registry.register(
+            "depth_cache_hits",
+            "Depth results served from the frame hash cache",
+            depth_cache_hits.clone(),
+        );
+
+        let depth_inference_duration = Histogram::new(INFERENCE_BUCKETS);
+        registry.register(
+            "depth_inference_duration_seconds",
+            "Depth inference latency",
+            depth_inference_duration.clone(),
+        );
+
+        let gil_wait_duration = Histogram::new(GIL_BUCKETS);
+        registry.register(
+            "gil_wait_duration_seconds",
+            "Time spent waiting to acquire the Python GIL",
+            gil_wait_duration.clone(),
+        );
+
+        let gil_held_duration = Histogram::new(GIL_BUCKETS);
+        registry.register(
+            "gil_held_duration_seconds",
+            "Time the Python GIL was held per call",
+            gil_held_duration.clone(),
+        );
+
+        let depth_model_loaded = Gauge::default();
+        registry.register(
+            "depth_model_loaded",
+            "1 when the depth model is loaded",
+            depth_model_loaded.clone(),
+        );
+
+        let depth_queue_depth = Gauge::default();
+        registry.register(
+            "depth_queue_depth",
+            "Depth frames queued or running",
+            depth_queue_depth.clone(),
+        );
+
+        let depth_compression_ratio = Histogram::new(COMPRESSION_RATIO_BUCKETS);
+        registry.register(
+            "depth_compression_ratio",
+            "Raw over zstd size of /ws/depth results",
+            depth_compression_ratio.clone(),
+        );
+
+        let ws_depth_connections = Gauge::default();
+        registry.register(
+            "ws_depth_connections",
+            "Open /ws/depth connections",
+            ws_depth_connections.clone(),
+        );
+
+        let memory_rss_bytes = Gauge::default();
+        registry.register(
+            "memory_rss_bytes",
+            "Resident set size, refreshed on scrape",
+            memory_rss_bytes.clone(),
+        );
+
+        Self {
+            registry,
+            depth_inferences,
+            depth_cache_hits,
+            depth_inference_duration,
+            gil_wait_duration,
+            gil_held_duration,
+            depth_model_loaded,
+            depth_queue_depth,
+            depth_compression_ratio,
+            ws_depth_connections,
+            memory_rss_bytes,
+        }
+    }
+}
+
+/// Global metrics, registered on first use
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// Define routes for this endpoint
+/// Path: /metrics
+pub fn routes() -> Router {
+    Router::new().route("/metrics", get(handler))
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "info",
+    responses((status = 200, description = "Prometheus metrics", content_type = "application/openmetrics-text"))
+)]
+async fn handler() -> Response {
+    match rss_bytes() {
+        Ok(rss) => {
+            metrics().memory_rss_bytes.set(rss as i64);
+        }
+        Err(e) => error!("[METRICS] Reading RSS failed: {}", e),
+    }
+    encode_registry(&metrics().registry)
+}
+
+/// OpenMetrics text for `registry`
+fn encode_registry(registry: &Registry) -> Response {
+    let mut body = String::new();
+    match encode(&mut body, registry) {
+        Ok(()) => (
+            [(
+                header::CONTENT_TYPE,
+                "application/openmetrics-text; version=1.0.0; charset=utf-8",
+            )],
+            body,
+        )
+            .into_response(),
+        Err(e) => {
+            error!("[METRICS] Encode failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use axum::{Router, response::Response, routing::get};
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::registry::Registry;
+use std::sync::OnceLock;
+#[cfg(tokio_unstable)]
+use std::sync::atomic::AtomicU64;
+use std::time::Duration;
+use tokio::runtime::Handle;
+use tokio_metrics::RuntimeMonitor;
+
+use super::encode_registry;
+
+/// How often the runtime monitor is sampled
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Tokio runtime metrics, kept apart from the application registry
+struct RuntimeMetrics {
+    registry: Registry,
+    worker_park: Counter,
+    workers: Gauge,
+    live_tasks: Gauge,
+    global_queue_depth: Gauge,
+    /// Needs RUSTFLAGS="--cfg tokio_unstable"
+    #[cfg(tokio_unstable)]
+    task_poll_duration: Gauge<f64, AtomicU64>,
+    #[cfg(tokio_unstable)]
+    blocking_queue_depth: Gauge,
+    #[cfg(tokio_unstable)]
+    spawn: Counter,
+}
+
+impl RuntimeMetrics {
+    fn new() -> Self {
+        let mut registry = Registry::with_prefix("tokio");
+
+        let worker_park = Counter::default();
+        registry.register(
+            "worker_park",
+            "Times worker threads parked",
+            worker_park.clone(),
+        );
+
+        let workers = Gauge::default();
+        registry.register("workers", "Runtime worker threads", workers.clone());
+
+        let live_tasks = Gauge::default();
+        registry.register(
+            "live_tasks",
+            "Tasks alive in the runtime",
+            live_tasks.clone(),
+        );
+
+        let global_queue_depth = Gauge::default();
+        registry.register(
+            "global_queue_depth",
+            "Tasks waiting in the global injection queue",
+            global_queue_depth.clone(),
+        );
+
+        #[cfg(tokio_unstable)]
+        let task_poll_duration = Gauge::<f64, AtomicU64>::default();
+        #[cfg(tokio_unstable)]
+        registry.register(
+            "task_poll_duration_seconds",
+            "Mean task poll duration over the last sample interval",
+            task_poll_duration.clone(),
+        );
+
+        #[cfg(tokio_unstable)]
+        let blocking_queue_depth = Gauge::default();
+        #[cfg(tokio_unstable)]
+        registry.register(
+            "blocking_queue_depth",
+            "Tasks waiting for a blocking thread",
+            blocking_queue_depth.clone(),
+        );
+
+        #[cfg(tokio_unstable)]
+        let spawn = Counter::default();
+        #[cfg(tokio_unstable)]
+        registry.register("spawn", "Tasks spawned", spawn.clone());
+
+        Self {
+            registry,
+            worker_park,
+            workers,
+            live_tasks,
+            global_queue_depth,
+            #[cfg(tokio_unstable)]
+            task_poll_duration,
+            #[cfg(tokio_unstable)]
+            blocking_queue_depth,
+            #[cfg(tokio_unstable)]
+            spawn,
+        }
+    }
+}
+
+fn runtime_metrics() -> &'static RuntimeMetrics {
+    static METRICS: OnceLock<RuntimeMetrics> = OnceLock::new();
+    METRICS.get_or_init(RuntimeMetrics::new)
+}
+
+/// Sample `handle` every 10 s for the lifetime of the runtime
+pub fn spawn_sampler(handle: &Handle) {
+    let monitor = RuntimeMonitor::new(handle);
+    #[cfg(tokio_unstable)]
+    let handle = handle.clone();
+    tokio::spawn(async move {
+        let metrics = runtime_metrics();
+        let mut intervals = monitor.intervals();
+        #[cfg(tokio_unstable)]
+        let mut spawned = 0;
+        let mut ticker = tokio::time::interval(SAMPLE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let Some(interval) = intervals.next() else {
+                break;
+            };
+            metrics.worker_park.inc_by(interval.total_park_count);
+            metrics.workers.set(interval.workers_count as i64);
+            metrics.live_tasks.set(interval.live_tasks_count as i64);
+            metrics
+                .global_queue_depth
+                .set(interval.global_queue_depth as i64);
+            #[cfg(tokio_unstable)]
+            {
+                metrics
+                    .task_poll_duration
+                    .set(interval.mean_poll_duration.as_secs_f64());
+                metrics
+                    .blocking_queue_depth
+                    .set(interval.blocking_queue_depth as i64);
+                let total = handle.metrics().spawned_tasks_count();
+                metrics.spawn.inc_by(total - spawned);
+                spawned = total;
+            }
+        }
+    });
+}
+
+/// Define routes for this endpoint
+/// Path: /metrics/runtime
+pub fn routes() -> Router {
+    Router::new().route("/metrics/runtime", get(handler))
+}
+
+async fn handler() -> Response {
+    encode_registry(&runtime_metrics().registry)
+}
@@ -0,0 +1,188 @@
+use axum::{
+    Extension,
+    body::HttpBody,
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tower_http::request_id::RequestId;
+use tracing::info;
+
+use super::client_cert::ClientSubject;
+use super::jwt_auth::redacted_uri;
+use super::proxy_trust::RealClientIp;
+use crate::config::IpRedaction;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// Redacts client IPs according to the configured mode
+pub struct IpRedactor {
+    mode: IpRedaction,
+    secret: [u8; 32],
+}
+
+pub type SharedIpRedactor = Arc<IpRedactor>;
+
+impl IpRedactor {
+    /// The HMAC secret for `Full` is random per process, so pseudonyms also
+    /// change on every restart and cannot be correlated across runs
+    pub fn new(mode: IpRedaction) -> anyhow::Result<Self> {
+        let mut secret = [0u8; 32];
+        getrandom::fill(&mut secret)
+            .map_err(|e| anyhow::anyhow!("Failed to generate IP redaction secret: {}", e))?;
+        info!("[ACCESS] Client IP redaction: {:?}", mode);
+        Ok(Self { mode, secret })
+    }
+
+    /// Render an IP for logging
+    pub fn redact(&self, ip: IpAddr) -> String {
+        match self.mode {
+            IpRedaction::None => ip.to_string(),
+            IpRedaction::Partial => match ip {
+                IpAddr::V4(v4) => {
+                    let [a, b, c, _] = v4.octets();
+                    Ipv4Addr::new(a, b, c, 0).to_string()
+                }
+                IpAddr::V6(v6) => {
+                    // Keep the leading 48 bits (routing prefix)
+                    let masked = v6.to_bits() & (!0u128 << 80);
+                    Ipv6Addr::from_bits(masked).to_string()
+                }
+            },
+            IpRedaction::Full => {
+                let day = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs() / SECS_PER_DAY)
+                    .unwrap_or(0);
+                self.pseudonym(ip, day)
+            }
+        }
+    }
+
+    /// Stable for one UTC day (and process), unlinkable across days
+    fn pseudonym(&self, ip: IpAddr, day: u64) -> String {
+        let mut day_key =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC accepts any key length");
+        day_key.update(&day.to_le_bytes());
+        let day_key = day_key.finalize().into_bytes();
+
+        let mut mac = HmacSha256::new_from_slice(&day_key).expect("HMAC accepts any key length");
+        match ip {
+            IpAddr::V4(v4) => mac.update(&v4.octets()),
+            IpAddr::V6(v6) => mac.update(&v6.octets()),
+        }
+        let digest = mac.finalize().into_bytes();
+
+        let hex: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("anon-{}", hex)
+    }
+}
+
+/// Log each request with its (redacted) client IP, status, size and latency
+pub async fn log_requests(
+    State(redactor): State<SharedIpRedactor>,
+    client_ip: Option<Extension<RealClientIp>>,
+    request_id: Option<Extension<RequestId>>,
+    client_subject: Option<Extension<ClientSubject>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let method = req.method().clone();
+    let uri = redacted_uri(req.uri());
+    let start = Instant::now();
+
+    let response = next.run(req).await;
+
+    let duration_ms = start.elapsed().as_millis() as u64;
+    let remote_addr = match client_ip {
+        Some(Extension(RealClientIp(ip))) => redactor.redact(ip),
+        None => "-".to_string(),
+    };
+    let request_id = request_id
+        .and_then(|Extension(id)| id.header_value().to_str().ok().map(str::to_string))
+        .unwrap_or_else(|| "-".to_string());
+    let client_subject = match client_subject {
+        Some(Extension(ClientSubject(subject))) => subject,
+        None => "-".to_string(),
+    };
+    let status = response.status().as_u16();
+    // Streamed bodies (files, proxy) have no exact size up front
+    let response_bytes = response.body().size_hint().exact();
+    info!(
+        remote_addr = %remote_addr,
+        method = %method,
+        uri = %uri,
+        status,
+        response_bytes,
+        duration_ms,
+        request_id = %request_id,
+        client_subject = %client_subject,
+        "[ACCESS]"
+    );
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn none_keeps_the_address() {
+        let redactor = IpRedactor::new(IpRedaction::None).unwrap();
+        assert_eq!(redactor.redact(ip("203.0.113.57")), "203.0.113.57");
+    }
+
+    #[test]
+    fn partial_masks_ipv4_to_24_bits() {
+        let redactor = IpRedactor::new(IpRedaction::Partial).unwrap();
+        assert_eq!(redactor.redact(ip("203.0.113.57")), "203.0.113.0");
+        assert_eq!(redactor.redact(ip("10.1.2.255")), "10.1.2.0");
+    }
+
+    #[test]
+    fn partial_keeps_the_ipv6_routing_prefix() {
+        let redactor = IpRedactor::new(IpRedaction::Partial).unwrap();
+        assert_eq!(
+            redactor.redact(ip("2001:db8:abcd:12:34:56:78:9a")),
+            "2001:db8:abcd::"
+        );
+        assert_eq!(redactor.redact(ip("2001:db8:ab00::1")), "2001:db8:ab00::");
+    }
+
+    #[test]
+    fn full_pseudonym_is_stable_within_a_day() {
+        let redactor = IpRedactor::new(IpRedaction::Full).unwrap();
+        let client = ip("203.0.113.57");
+        let today = redactor.pseudonym(client, 20_000);
+        assert!(today.starts_with("anon-") && today.len() == 21, "{}", today);
+        assert!(!today.contains("203.0.113"));
+        assert_eq!(redactor.pseudonym(client, 20_000), today);
+        assert_eq!(redactor.redact(client), redactor.redact(client));
+
+        assert_ne!(redactor.pseudonym(ip("203.0.113.58"), 20_000), today);
+        assert_ne!(redactor.pseudonym(client, 20_001), today);
+    }
+
+    #[test]
+    fn full_pseudonym_changes_with_the_process_secret() {
+        let client = ip("2001:db8::1");
+        let first = IpRedactor::new(IpRedaction::Full).unwrap();
+        let restarted = IpRedactor::new(IpRedaction::Full).unwrap();
+        assert_ne!(
+            first.pseudonym(client, 20_000),
+            restarted.pseudonym(client, 20_000)
+        );
+    }
+}
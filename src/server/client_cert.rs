@@ -0,0 +1,73 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::{Either, Ready, ready};
+use rustls::pki_types::CertificateDer;
+use std::convert::Infallible;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tracing::debug;
+
+/// Leaf certificate the client presented during the TLS handshake
+#[derive(Debug, Clone)]
+pub struct PeerCertificate(pub CertificateDer<'static>);
+
+/// Subject DN of an authenticated client certificate, for downstream logging
+#[derive(Debug, Clone)]
+pub struct ClientSubject(pub String);
+
+/// Layer rejecting requests whose connection carried no client certificate.
+/// The chain was already verified against `client_ca` by rustls during the handshake.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClientCertLayer;
+
+impl<S> Layer<S> for ClientCertLayer {
+    type Service = ClientCert<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientCert { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct ClientCert<S> {
+    inner: S,
+}
+
+fn subject(cert: &CertificateDer<'_>) -> anyhow::Result<String> {
+    let (_, parsed) = x509_parser::parse_x509_certificate(cert.as_ref())
+        .map_err(|e| anyhow::anyhow!("Unparseable client certificate: {}", e))?;
+    Ok(parsed.subject().to_string())
+}
+
+impl<S> Service<Request<Body>> for ClientCert<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible>,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Either<Ready<Result<Response, Infallible>>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let subject = match req.extensions().get::<PeerCertificate>() {
+            Some(PeerCertificate(cert)) => subject(cert),
+            None => Err(anyhow::anyhow!("No client certificate")),
+        };
+        match subject {
+            Ok(subject) => {
+                req.extensions_mut().insert(ClientSubject(subject));
+                Either::Right(self.inner.call(req))
+            }
+            Err(e) => {
+                debug!("[TLS] Rejected {}: {}", req.uri().path(), e);
+                Either::Left(ready(Ok(StatusCode::UNAUTHORIZED.into_response())))
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::config::{CorsPolicy, CorsPolicyMap};
+
+/// CorsLayer for a route path, using its override if configured
+pub fn layer_for(policies: &CorsPolicyMap, path: &str) -> anyhow::Result<CorsLayer> {
+    build_layer(policies.policy_for(path))
+        .map_err(|e| anyhow::anyhow!("Invalid CORS policy for {}: {}", path, e))
+}
+
+fn build_layer(policy: &CorsPolicy) -> anyhow::Result<CorsLayer> {
+    let CorsPolicy {
+        origins,
+        methods,
+        headers,
+    } = policy;
+
+    let allow_origin = if origins.is_empty() {
+        AllowOrigin::mirror_request()
+    } else {
+        let origins = origins
+            .iter()
+            .map(|origin| {
+                origin
+                    .parse::<HeaderValue>()
+                    .map_err(|e| anyhow::anyhow!("origin '{}': {}", origin, e))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        AllowOrigin::list(origins)
+    };
+    let methods = methods
+        .iter()
+        .map(|method| {
+            method
+                .parse::<Method>()
+                .map_err(|e| anyhow::anyhow!("method '{}': {}", method, e))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let headers = headers
+        .iter()
+        .map(|name| {
+            name.parse::<HeaderName>()
+                .map_err(|e| anyhow::anyhow!("header '{}': {}", name, e))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CorsPolicyMap;
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, header},
+        routing::get,
+    };
+    use std::collections::HashMap;
+    use tower::ServiceExt;
+
+    const APP_ORIGIN: &str = "https://app.example.com";
+
+    /// Preflight from APP_ORIGIN, returning the allowed origin if any
+    async fn preflight(app: Router, path: &str) -> Option<HeaderValue> {
+        let request = Request::options(path)
+            .header(header::ORIGIN, APP_ORIGIN)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .cloned()
+    }
+
+    #[tokio::test]
+    async fn override_applies_to_its_route_only() {
+        let CorsPolicy {
+            origins: _,
+            methods,
+            headers,
+        } = CorsPolicy::default();
+        let allow = |origin: &str| CorsPolicy {
+            origins: vec![origin.to_string()],
+            methods: methods.clone(),
+            headers: headers.clone(),
+        };
+        let policies = CorsPolicyMap {
+            default: allow("https://other.example.com"),
+            overrides: HashMap::from([("/readyz".to_string(), allow(APP_ORIGIN))]),
+        };
+
+        let app = Router::new()
+            .merge(
+                Router::new()
+                    .route("/healthz", get(|| async { "ok" }))
+                    .route_layer(layer_for(&policies, "/healthz").unwrap()),
+            )
+            .merge(
+                Router::new()
+                    .route("/readyz", get(|| async { "ok" }))
+                    .route_layer(layer_for(&policies, "/readyz").unwrap()),
+            );
+
+        assert_eq!(
+            preflight(app.clone(), "/readyz").await,
+            Some(HeaderValue::from_static(APP_ORIGIN))
+        );
+        assert_eq!(preflight(app, "/healthz").await, None);
+    }
+}
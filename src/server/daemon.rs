@@ -0,0 +1,33 @@
+//! Unix-only background mode. Windows has no fork; run the server as a
+//! service (e.g. via NSSM or `sc create`) instead of passing --daemonize.
+
+use std::path::{Path, PathBuf};
+use tracing::{error, info};
+
+/// PID file removed when dropped, i.e. after graceful shutdown
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        match std::fs::remove_file(&self.path) {
+            Ok(()) => info!("[DAEMON] Removed PID file {:?}", self.path),
+            Err(e) => error!("[DAEMON] Failed to remove PID file {:?}: {}", self.path, e),
+        }
+    }
+}
+
+/// Fork into the background and record the child's PID.
+/// Must run before the tokio runtime starts any threads.
+pub fn daemonize(pid_path: &Path) -> anyhow::Result<PidFile> {
+    // Keep the working directory so config.toml, models/ and python/ still resolve
+    nix::unistd::daemon(true, false).map_err(|e| anyhow::anyhow!("daemon() failed: {}", e))?;
+    let pid = std::process::id();
+    std::fs::write(pid_path, format!("{}\n", pid))
+        .map_err(|e| anyhow::anyhow!("Failed to write PID file {:?}: {}", pid_path, e))?;
+    info!("[DAEMON] Running as PID {}, PID file {:?}", pid, pid_path);
+    Ok(PidFile {
+        path: pid_path.to_path_buf(),
+    })
+}
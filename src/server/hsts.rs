@@ -0,0 +1,239 @@
+use axum::body::Body;
+use axum::http::{HeaderValue, header};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use serde::Deserialize;
+use tower_http::set_header::SetResponseHeaderLayer;
+use tracing::{info, warn};
+
+use crate::config::HstsConfig;
+
+/// One year, the minimum max-age accepted for preloading
+pub const PRELOAD_MIN_MAX_AGE: u64 = 31_536_000;
+
+const PRELOAD_STATUS_API: &str = "https://hstspreload.org/api/v2/status";
+
+/// Strict-Transport-Security policy sent by the server
+#[derive(Debug, Clone, Copy)]
+pub struct HstsPolicy {
+    pub max_age: u64,
+    pub include_subdomains: bool,
+    pub preload: bool,
+}
+
+impl Default for HstsPolicy {
+    fn default() -> Self {
+        Self::from_config(&HstsConfig::default())
+    }
+}
+
+impl HstsPolicy {
+    pub fn from_config(config: &HstsConfig) -> Self {
+        let HstsConfig {
+            max_age,
+            include_subdomains,
+            preload,
+            domain: _,
+        } = config;
+        Self {
+            max_age: *max_age,
+            include_subdomains: *include_subdomains,
+            preload: *preload,
+        }
+    }
+
+    /// Adds the header to every response; mount on TLS listeners only, browsers ignore it over HTTP
+    pub fn layer(&self) -> anyhow::Result<SetResponseHeaderLayer<HeaderValue>> {
+        Ok(SetResponseHeaderLayer::if_not_present(
+            header::STRICT_TRANSPORT_SECURITY,
+            HeaderValue::from_str(&self.header_value())?,
+        ))
+    }
+
+    pub fn header_value(&self) -> String {
+        let Self {
+            max_age,
+            include_subdomains,
+            preload,
+        } = self;
+        let mut value = format!("max-age={}", max_age);
+        if *include_subdomains {
+            value.push_str("; includeSubDomains");
+        }
+        if *preload {
+            value.push_str("; preload");
+        }
+        value
+    }
+
+    /// Requirements from hstspreload.org that this policy misses
+    fn preload_violations(&self) -> Vec<String> {
+        let Self {
+            max_age,
+            include_subdomains,
+            preload,
+        } = self;
+        let mut violations = Vec::new();
+        if *max_age < PRELOAD_MIN_MAX_AGE {
+            violations.push(format!(
+                "max-age={} is below {}",
+                max_age, PRELOAD_MIN_MAX_AGE
+            ));
+        }
+        if !include_subdomains {
+            violations.push("includeSubDomains is not set".to_string());
+        }
+        if !preload {
+            violations.push("preload directive is not set".to_string());
+        }
+        violations
+    }
+}
+
+/// Status reported by the hstspreload.org API
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum PreloadStatus {
+    Unknown,
+    Pending,
+    Preloaded,
+    Rejected,
+    Removed,
+}
+
+#[derive(Debug, Deserialize)]
+struct PreloadStatusResponse {
+    status: PreloadStatus,
+}
+
+/// Lookup URL with the domain percent-encoded
+fn status_uri(domain: &str) -> String {
+    let query = form_urlencoded::Serializer::new(String::new())
+        .append_pair("domain", domain)
+        .finish();
+    format!("{}?{}", PRELOAD_STATUS_API, query)
+}
+
+async fn fetch_preload_status(domain: &str) -> anyhow::Result<PreloadStatus> {
+    let connector = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_webpki_roots()
+        .https_only()
+        .enable_http1()
+        .build();
+    let client = Client::builder(TokioExecutor::new()).build::<_, Body>(connector);
+
+    let uri = status_uri(domain).parse::<hyper::Uri>()?;
+    let response = client.get(uri).await?;
+    anyhow::ensure!(
+        response.status().is_success(),
+        "hstspreload.org returned {}",
+        response.status()
+    );
+
+    let body = axum::body::to_bytes(Body::new(response.into_body()), 64 * 1024).await?;
+    let PreloadStatusResponse { status } = serde_json::from_slice(&body)?;
+    Ok(status)
+}
+
+/// Startup check for HSTS preloading; logs only, never fails
+pub async fn check_preload(policy: HstsPolicy, domain: Option<String>) {
+    let violations = policy.preload_violations();
+    if violations.is_empty() {
+        info!(
+            "[HSTS] Header meets preload requirements: {}",
+            policy.header_value()
+        );
+    }
+    for violation in &violations {
+        warn!("[HSTS] Not eligible for preload: {}", violation);
+    }
+
+    let Some(domain) = domain else {
+        warn!("[HSTS] hsts.domain not set, skipping preload list check");
+        return;
+    };
+
+    match fetch_preload_status(&domain).await {
+        Ok(PreloadStatus::Preloaded) => info!("[HSTS] {} is on the preload list", domain),
+        Ok(PreloadStatus::Pending) => info!("[HSTS] {} is pending preload inclusion", domain),
+        Ok(status) => warn!(
+            "[HSTS] {} is not on the preload list (status: {:?}); submit it at https://hstspreload.org",
+            domain, status
+        ),
+        Err(e) => warn!(
+            "[HSTS] Could not query preload status for {}: {}",
+            domain, e
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_age: u64, include_subdomains: bool, preload: bool) -> HstsPolicy {
+        HstsPolicy {
+            max_age,
+            include_subdomains,
+            preload,
+        }
+    }
+
+    #[test]
+    fn header_value_lists_enabled_directives() {
+        assert_eq!(policy(600, false, false).header_value(), "max-age=600");
+        assert_eq!(
+            policy(PRELOAD_MIN_MAX_AGE, true, true).header_value(),
+            "max-age=31536000; includeSubDomains; preload"
+        );
+    }
+
+    #[test]
+    fn preload_violations_follow_the_config() {
+        let config = HstsConfig {
+            max_age: 600,
+            include_subdomains: false,
+            preload: true,
+            domain: None,
+        };
+        assert_eq!(
+            HstsPolicy::from_config(&config).preload_violations(),
+            [
+                "max-age=600 is below 31536000",
+                "includeSubDomains is not set"
+            ]
+        );
+        assert!(
+            policy(PRELOAD_MIN_MAX_AGE, true, true)
+                .preload_violations()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn layer_sets_the_header() {
+        use axum::{Router, http::Request, routing::get};
+        use tower::ServiceExt;
+
+        let policy = policy(PRELOAD_MIN_MAX_AGE, true, false);
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(policy.layer().unwrap());
+        let response = app
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[header::STRICT_TRANSPORT_SECURITY],
+            "max-age=31536000; includeSubDomains"
+        );
+    }
+
+    #[test]
+    fn status_uri_encodes_the_domain() {
+        assert_eq!(
+            status_uri("example.com&x=1"),
+            "https://hstspreload.org/api/v2/status?domain=example.com%26x%3D1"
+        );
+    }
+}
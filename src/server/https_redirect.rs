@@ -0,0 +1,58 @@
+use super::hsts::HstsPolicy;
+use axum::{
+    Router,
+    extract::{Request, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+const DEFAULT_HTTPS_PORT: u16 = 443;
+
+#[derive(Clone)]
+struct RedirectTarget {
+    https_port: u16,
+    hsts: HstsPolicy,
+}
+
+/// Router that answers every request with a 301 to the HTTPS origin
+pub fn redirect_router(https_port: u16, hsts: HstsPolicy) -> Router {
+    Router::new()
+        .fallback(redirect_to_https)
+        .with_state(RedirectTarget { https_port, hsts })
+}
+
+async fn redirect_to_https(State(target): State<RedirectTarget>, req: Request) -> Response {
+    let RedirectTarget { https_port, hsts } = target;
+    let Some(host) = req
+        .headers()
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return (StatusCode::BAD_REQUEST, "Missing Host header").into_response();
+    };
+
+    // Strip any port from Host, handling bracketed IPv6 literals
+    let hostname = match host.rsplit_once(':') {
+        Some((name, port)) if !port.contains(']') && port.parse::<u16>().is_ok() => name,
+        _ => host,
+    };
+    let authority = match https_port {
+        DEFAULT_HTTPS_PORT => hostname.to_string(),
+        port => format!("{}:{}", hostname, port),
+    };
+    let path_query = req
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+    let location = format!("https://{}{}", authority, path_query);
+
+    (
+        StatusCode::MOVED_PERMANENTLY,
+        [
+            (header::LOCATION, location),
+            (header::STRICT_TRANSPORT_SECURITY, hsts.header_value()),
+        ],
+    )
+        .into_response()
+}
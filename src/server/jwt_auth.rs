@@ -0,0 +1,236 @@
+use axum::{
+    body::Body,
+    http::{Request, StatusCode, Uri, header},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::{Either, Ready, ready};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tracing::debug;
+
+use crate::config::JwtConfig;
+
+/// Query parameter carrying the token, browsers cannot set headers on WebSocket upgrades
+const TOKEN_QUERY_PARAM: &str = "token";
+
+/// Claims attached to authenticated requests
+#[derive(Debug, Clone, Deserialize)]
+pub struct JwtClaims {
+    pub sub: Option<String>,
+    pub exp: u64,
+}
+
+struct JwtKeys {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+/// Layer rejecting requests without a valid bearer token
+#[derive(Clone)]
+pub struct JwtAuthLayer {
+    keys: Arc<JwtKeys>,
+}
+
+impl JwtAuthLayer {
+    pub fn new(config: &JwtConfig) -> anyhow::Result<Self> {
+        let JwtConfig { secret, algorithm } = config;
+        let secret = secret.as_bytes();
+        // HMAC uses the raw secret, everything else a PEM public key
+        let key = match algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                DecodingKey::from_secret(secret)
+            }
+            Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512 => DecodingKey::from_rsa_pem(secret)?,
+            Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(secret)?,
+            Algorithm::EdDSA => DecodingKey::from_ed_pem(secret)?,
+        };
+        Ok(Self {
+            keys: Arc::new(JwtKeys {
+                key,
+                validation: Validation::new(*algorithm),
+            }),
+        })
+    }
+}
+
+impl<S> Layer<S> for JwtAuthLayer {
+    type Service = JwtAuth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        JwtAuth {
+            inner,
+            keys: self.keys.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct JwtAuth<S> {
+    inner: S,
+    keys: Arc<JwtKeys>,
+}
+
+/// Bearer token from the Authorization header, else the `token` query parameter
+fn extract_token<B>(req: &Request<B>) -> Option<&str> {
+    let from_header = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    from_header.or_else(|| {
+        req.uri()
+            .query()?
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(name, _)| *name == TOKEN_QUERY_PARAM)
+            .map(|(_, value)| value)
+    })
+}
+
+/// URI for logs and spans with the `token` query value masked
+pub(crate) fn redacted_uri(uri: &Uri) -> String {
+    let Some(query) = uri.query() else {
+        return uri.to_string();
+    };
+    let query: Vec<&str> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((TOKEN_QUERY_PARAM, _)) => "token=REDACTED",
+            _ => pair,
+        })
+        .collect();
+    format!("{}?{}", uri.path(), query.join("&"))
+}
+
+impl<S> Service<Request<Body>> for JwtAuth<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible>,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Either<Ready<Result<Response, Infallible>>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let JwtKeys { key, validation } = self.keys.as_ref();
+        let claims = match extract_token(&req) {
+            Some(token) => decode::<JwtClaims>(token, key, validation).map(|data| data.claims),
+            None => {
+                debug!("[AUTH] Missing token for {}", req.uri().path());
+                return Either::Left(ready(Ok(StatusCode::UNAUTHORIZED.into_response())));
+            }
+        };
+        match claims {
+            Ok(claims) => {
+                req.extensions_mut().insert(claims);
+                Either::Right(self.inner.call(req))
+            }
+            Err(e) => {
+                debug!("[AUTH] Rejected token for {}: {}", req.uri().path(), e);
+                Either::Left(ready(Ok(StatusCode::UNAUTHORIZED.into_response())))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, routing::get};
+    use jsonwebtoken::{EncodingKey, Header, encode};
+    use serde::Serialize;
+    use std::time::{SystemTime, UNIX_EPOCH};
+    use tower::ServiceExt;
+
+    const SECRET: &str = "test-secret";
+
+    #[derive(Serialize)]
+    struct TestClaims {
+        sub: &'static str,
+        exp: u64,
+    }
+
+    /// HS256 token signed with `secret`, expiring `ttl_secs` from now
+    fn token(secret: &str, ttl_secs: i64) -> String {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let claims = TestClaims {
+            sub: "tester",
+            exp: now.saturating_add_signed(ttl_secs),
+        };
+        let key = EncodingKey::from_secret(secret.as_bytes());
+        encode(&Header::default(), &claims, &key).unwrap()
+    }
+
+    async fn status(uri: &str, bearer: Option<&str>) -> StatusCode {
+        let config = JwtConfig {
+            secret: SECRET.to_string(),
+            algorithm: Algorithm::HS256,
+        };
+        let app = Router::new()
+            .route("/ws/depth", get(|| async { "ok" }))
+            .route_layer(JwtAuthLayer::new(&config).unwrap());
+        let mut req = Request::builder().uri(uri);
+        if let Some(bearer) = bearer {
+            req = req.header(header::AUTHORIZATION, format!("Bearer {}", bearer));
+        }
+        app.oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn valid_token_is_accepted() {
+        let token = token(SECRET, 3600);
+        assert_eq!(status("/ws/depth", Some(&token)).await, StatusCode::OK);
+        let uri = format!("/ws/depth?format=u8&token={}", token);
+        assert_eq!(status(&uri, None).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_rejected() {
+        // Beyond the default 60 s leeway
+        let token = token(SECRET, -3600);
+        assert_eq!(
+            status("/ws/depth", Some(&token)).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_secret_is_rejected() {
+        let token = token("other-secret", 3600);
+        assert_eq!(
+            status("/ws/depth", Some(&token)).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_token_is_rejected() {
+        assert_eq!(status("/ws/depth", None).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn redacted_uri_masks_only_the_token() {
+        let uri: Uri = "/ws/depth?format=u16&token=abc.def.ghi".parse().unwrap();
+        assert_eq!(redacted_uri(&uri), "/ws/depth?format=u16&token=REDACTED");
+        let uri: Uri = "/healthz".parse().unwrap();
+        assert_eq!(redacted_uri(&uri), "/healthz");
+    }
+}
@@ -1,7 +1,23 @@
+use crate::config::ServerConfig;
 use axum::Router;
 
+pub mod access_log;
+pub mod client_cert;
+pub mod cors;
+#[cfg(unix)]
+pub mod daemon;
+pub mod hsts;
+pub mod https_redirect;
+pub mod jwt_auth;
+pub mod proxy_trust;
+pub mod response_transform;
 pub mod route_builder;
+pub mod routing_rules;
+pub mod tls;
+pub mod trace_context;
+#[cfg(unix)]
+pub mod unix_listener;
 
-pub async fn build_router() -> Router {
-    route_builder::register_routes().await
+pub async fn build_router(config: &ServerConfig) -> anyhow::Result<Router> {
+    route_builder::register_routes(config).await
 }
@@ -0,0 +1,189 @@
+use axum::{
+    Extension,
+    extract::{ConnectInfo, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use ipnetwork::IpNetwork;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tracing::warn;
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Client IP after resolving trusted reverse proxies
+#[derive(Debug, Clone, Copy)]
+pub struct RealClientIp(pub IpAddr);
+
+/// Layer inserting a `RealClientIp` extension into each request
+#[derive(Clone)]
+pub struct ProxyTrustLayer {
+    trusted_proxies: Arc<Vec<IpNetwork>>,
+}
+
+impl ProxyTrustLayer {
+    pub fn new(trusted_proxies: Vec<IpNetwork>) -> Self {
+        Self {
+            trusted_proxies: Arc::new(trusted_proxies),
+        }
+    }
+}
+
+impl<S> Layer<S> for ProxyTrustLayer {
+    type Service = ProxyTrust<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ProxyTrust {
+            inner,
+            trusted_proxies: self.trusted_proxies.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ProxyTrust<S> {
+    inner: S,
+    trusted_proxies: Arc<Vec<IpNetwork>>,
+}
+
+impl<S> ProxyTrust<S> {
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|net| net.contains(ip))
+    }
+
+    /// Rightmost untrusted X-Forwarded-For hop when the peer is a trusted proxy
+    fn resolve<B>(&self, req: &Request<B>) -> Option<IpAddr> {
+        let ConnectInfo(peer) = req.extensions().get::<ConnectInfo<SocketAddr>>()?;
+        let peer_ip = peer.ip();
+        if !self.is_trusted(peer_ip) {
+            return Some(peer_ip);
+        }
+
+        let mut hops = Vec::new();
+        for value in req.headers().get_all(X_FORWARDED_FOR) {
+            let Ok(value) = value.to_str() else {
+                warn!(
+                    "[PROXY] Non-ASCII X-Forwarded-For from {}, using the peer address",
+                    peer_ip
+                );
+                return Some(peer_ip);
+            };
+            hops.extend(value.split(',').map(str::trim));
+        }
+
+        // Walk back from the nearest hop; anything unparseable could hide a spoofed client
+        let mut leftmost_trusted = None;
+        for hop in hops.iter().rev() {
+            match hop.parse::<IpAddr>() {
+                Ok(ip) if self.is_trusted(ip) => leftmost_trusted = Some(ip),
+                Ok(ip) => return Some(ip),
+                Err(_) => {
+                    warn!(
+                        "[PROXY] Unparseable X-Forwarded-For hop '{}' from {}, using the peer address",
+                        hop, peer_ip
+                    );
+                    return Some(peer_ip);
+                }
+            }
+        }
+        // Every hop trusted: the leftmost is the best we have
+        Some(leftmost_trusted.unwrap_or(peer_ip))
+    }
+}
+
+impl<S, B> Service<Request<B>> for ProxyTrust<S>
+where
+    S: Service<Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        if let Some(ip) = self.resolve(&req) {
+            req.extensions_mut().insert(RealClientIp(ip));
+        }
+        self.inner.call(req)
+    }
+}
+
+/// Reject clients whose resolved IP is not loopback; unix socket clients carry no IP and pass
+pub async fn require_loopback(
+    client_ip: Option<Extension<RealClientIp>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    match client_ip {
+        Some(Extension(RealClientIp(ip))) if !ip.is_loopback() => {
+            warn!(
+                "[AUTH] Rejected {} from non-loopback {}",
+                req.uri().path(),
+                ip
+            );
+            StatusCode::FORBIDDEN.into_response()
+        }
+        _ => next.run(req).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(trusted: &[&str], peer: &str, forwarded: &[&str]) -> IpAddr {
+        let proxy = ProxyTrust {
+            inner: (),
+            trusted_proxies: Arc::new(trusted.iter().map(|net| net.parse().unwrap()).collect()),
+        };
+        let mut builder = Request::builder();
+        for value in forwarded {
+            builder = builder.header(X_FORWARDED_FOR, *value);
+        }
+        let mut req = builder.body(()).unwrap();
+        let peer: SocketAddr = format!("{}:1234", peer).parse().unwrap();
+        req.extensions_mut().insert(ConnectInfo(peer));
+        proxy.resolve(&req).unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn untrusted_peer_ignores_the_header() {
+        let client = resolve(&["10.0.0.0/8"], "203.0.113.9", &["198.51.100.1"]);
+        assert_eq!(client, ip("203.0.113.9"));
+    }
+
+    #[test]
+    fn rightmost_untrusted_hop_wins() {
+        let client = resolve(
+            &["10.0.0.0/8"],
+            "10.0.0.1",
+            &["1.2.3.4, 198.51.100.1", "10.0.0.2"],
+        );
+        assert_eq!(client, ip("198.51.100.1"));
+    }
+
+    #[test]
+    fn all_trusted_falls_back_to_the_leftmost_hop() {
+        let client = resolve(&["10.0.0.0/8"], "10.0.0.1", &["10.0.0.3, 10.0.0.2"]);
+        assert_eq!(client, ip("10.0.0.3"));
+    }
+
+    #[test]
+    fn unparseable_hop_stops_at_the_peer() {
+        // Skipping the junk would hand the spoofed 1.2.3.4 through as the client
+        let client = resolve(&["10.0.0.0/8"], "10.0.0.1", &["1.2.3.4, unknown, 10.0.0.2"]);
+        assert_eq!(client, ip("10.0.0.1"));
+        let client = resolve(&["10.0.0.0/8"], "10.0.0.1", &["1.2.3.4,, 10.0.0.2"]);
+        assert_eq!(client, ip("10.0.0.1"));
+    }
+}
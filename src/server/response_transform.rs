@@ -0,0 +1,289 @@
+use axum::{
+    body::Body,
+    http::{HeaderValue, header},
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+use crate::config::ProxyTransform;
+
+/// Largest HTML body buffered for transformation
+const MAX_TRANSFORM_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Transform applied to proxied HTML responses
+#[derive(Clone)]
+pub enum ResponseTransform {
+    /// Insert markup before `</body>` (appended if missing)
+    InjectScript { html: String },
+    /// Replace every occurrence of `from` with `to`
+    ReplaceText { from: String, to: String },
+    /// Tag `<script>` elements with a fresh nonce and allow it in the CSP
+    AddNonce(fn() -> String),
+}
+
+impl ResponseTransform {
+    fn apply(&self, html: String, csp: &mut Option<String>) -> String {
+        match self {
+            Self::InjectScript { html: snippet } => match html.rfind("</body>") {
+                Some(idx) => {
+                    let (head, tail) = html.split_at(idx);
+                    format!("{}{}{}", head, snippet, tail)
+                }
+                None => html + snippet,
+            },
+            Self::ReplaceText { from, to } => html.replace(from.as_str(), to),
+            Self::AddNonce(generate) => {
+                let nonce = generate();
+                if let Some(policy) = csp.as_mut() {
+                    *policy = add_csp_nonce(policy, &nonce);
+                }
+                html.replace("<script", &format!("<script nonce=\"{}\"", nonce))
+            }
+        }
+    }
+}
+
+/// Append `'nonce-…'` to script-src (or add a script-src directive)
+fn add_csp_nonce(policy: &str, nonce: &str) -> String {
+    let source = format!("'nonce-{}'", nonce);
+    let mut found = false;
+    let directives: Vec<String> = policy
+        .split(';')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| {
+            if d.starts_with("script-src ") || d == "script-src" {
+                found = true;
+                format!("{} {}", d, source)
+            } else {
+                d.to_string()
+            }
+        })
+        .collect();
+    let mut policy = directives.join("; ");
+    if !found {
+        policy = format!("{}; script-src {}", policy, source);
+    }
+    policy
+}
+
+/// Random hex nonce for CSP
+pub fn generate_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).expect("OS random source unavailable");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl From<ProxyTransform> for ResponseTransform {
+    fn from(config: ProxyTransform) -> Self {
+        match config {
+            ProxyTransform::InjectScript { html } => Self::InjectScript { html },
+            ProxyTransform::ReplaceText { from, to } => Self::ReplaceText { from, to },
+            ProxyTransform::AddNonce => Self::AddNonce(generate_nonce),
+        }
+    }
+}
+
+/// Ordered transforms applied to HTML from the Next.js proxy
+#[derive(Clone, Default)]
+pub struct ResponseTransformPipeline {
+    transforms: Vec<ResponseTransform>,
+}
+
+pub type SharedTransformPipeline = Arc<ResponseTransformPipeline>;
+
+impl ResponseTransformPipeline {
+    /// Pipeline for the configured `proxy_transforms`
+    pub fn from_config(transforms: &[ProxyTransform]) -> Self {
+        if !transforms.is_empty() {
+            info!("[PROXY] Loaded {} response transforms", transforms.len());
+        }
+        Self {
+            transforms: transforms
+                .iter()
+                .cloned()
+                .map(ResponseTransform::from)
+                .collect(),
+        }
+    }
+
+    /// Append a transform (applied after existing ones)
+    pub fn push(&mut self, transform: ResponseTransform) {
+        self.transforms.push(transform);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+
+    /// Rewrite an uncompressed HTML response; anything else passes through
+    pub async fn apply(&self, response: Response) -> Response {
+        let is_html = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|ct| ct.starts_with("text/html"));
+        let is_encoded = response.headers().contains_key(header::CONTENT_ENCODING);
+        if self.is_empty() || !is_html || is_encoded {
+            return response;
+        }
+
+        let (mut parts, body) = response.into_parts();
+        let bytes = match axum::body::to_bytes(body, MAX_TRANSFORM_BODY_BYTES).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("[PROXY] Failed to buffer HTML for transforms: {}", e);
+                return (axum::http::StatusCode::BAD_GATEWAY, "Upstream body error")
+                    .into_response();
+            }
+        };
+        let html = match String::from_utf8(bytes.to_vec()) {
+            Ok(html) => html,
+            Err(_) => {
+                debug!("[PROXY] Skipping transforms for non UTF-8 HTML");
+                return Response::from_parts(parts, Body::from(bytes));
+            }
+        };
+
+        let mut csp = parts
+            .headers
+            .get(header::CONTENT_SECURITY_POLICY)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let html = self
+            .transforms
+            .iter()
+            .fold(html, |html, transform| transform.apply(html, &mut csp));
+
+        if let Some(policy) = csp.and_then(|p| HeaderValue::from_str(&p).ok()) {
+            parts
+                .headers
+                .insert(header::CONTENT_SECURITY_POLICY, policy);
+        }
+        parts.headers.remove(header::CONTENT_LENGTH);
+        Response::from_parts(parts, Body::from(html))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(content_type: &str, body: &'static str) -> Response {
+        Response::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    async fn body_text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    fn fixed_nonce() -> String {
+        "abc123".to_string()
+    }
+
+    fn pipeline(transforms: Vec<ResponseTransform>) -> ResponseTransformPipeline {
+        let mut pipeline = ResponseTransformPipeline::default();
+        for transform in transforms {
+            pipeline.push(transform);
+        }
+        pipeline
+    }
+
+    #[tokio::test]
+    async fn html_is_transformed_in_order() {
+        let pipeline = pipeline(vec![
+            ResponseTransform::ReplaceText {
+                from: "Hello".to_string(),
+                to: "Hi".to_string(),
+            },
+            ResponseTransform::InjectScript {
+                html: "<script>Hello()</script>".to_string(),
+            },
+        ]);
+        let html = "<html><body>Hello</body></html>";
+        let transformed = pipeline
+            .apply(response("text/html; charset=utf-8", html))
+            .await;
+        assert!(transformed.headers().get(header::CONTENT_LENGTH).is_none());
+        // The injected script comes after the replacement, so it keeps "Hello"
+        assert_eq!(
+            body_text(transformed).await,
+            "<html><body>Hi<script>Hello()</script></body></html>"
+        );
+    }
+
+    #[tokio::test]
+    async fn non_html_passes_through() {
+        let pipeline = pipeline(vec![ResponseTransform::ReplaceText {
+            from: "a".to_string(),
+            to: "b".to_string(),
+        }]);
+        for content_type in ["application/json", "text/plain", "text/css"] {
+            let transformed = pipeline.apply(response(content_type, "aaa")).await;
+            assert_eq!(
+                transformed.headers()[header::CONTENT_LENGTH],
+                "3",
+                "{}",
+                content_type
+            );
+            assert_eq!(body_text(transformed).await, "aaa");
+        }
+    }
+
+    #[tokio::test]
+    async fn compressed_html_passes_through() {
+        let pipeline = pipeline(vec![ResponseTransform::ReplaceText {
+            from: "a".to_string(),
+            to: "b".to_string(),
+        }]);
+        let mut compressed = response("text/html", "aaa");
+        compressed
+            .headers_mut()
+            .insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        assert_eq!(body_text(pipeline.apply(compressed).await).await, "aaa");
+    }
+
+    #[test]
+    fn inject_script_appends_without_body_tag() {
+        let transform = ResponseTransform::InjectScript {
+            html: "<script></script>".to_string(),
+        };
+        let html = transform.apply("<p>fragment</p>".to_string(), &mut None);
+        assert_eq!(html, "<p>fragment</p><script></script>");
+    }
+
+    #[tokio::test]
+    async fn nonce_is_added_to_scripts_and_csp() {
+        let pipeline = pipeline(vec![ResponseTransform::AddNonce(fixed_nonce)]);
+        let mut html = response("text/html", "<script src=\"/app.js\"></script>");
+        html.headers_mut().insert(
+            header::CONTENT_SECURITY_POLICY,
+            HeaderValue::from_static("default-src 'self'; script-src 'self'"),
+        );
+        let transformed = pipeline.apply(html).await;
+        assert_eq!(
+            transformed.headers()[header::CONTENT_SECURITY_POLICY],
+            "default-src 'self'; script-src 'self' 'nonce-abc123'"
+        );
+        assert_eq!(
+            body_text(transformed).await,
+            "<script nonce=\"abc123\" src=\"/app.js\"></script>"
+        );
+    }
+
+    #[test]
+    fn csp_without_script_src_gains_one() {
+        assert_eq!(
+            add_csp_nonce("default-src 'self';", "n"),
+            "default-src 'self'; script-src 'nonce-n'"
+        );
+    }
+}
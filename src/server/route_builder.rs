@@ -1,6 +1,7 @@
 use axum::{
     Router,
     extract::Request,
+    handler::Handler,
     http::StatusCode,
     response::{IntoResponse, Response},
     routing::get,
@@ -8,10 +9,26 @@ use axum::{
 use hyper_util::client::legacy::Client;
 use hyper_util::rt::TokioExecutor;
 use std::path::PathBuf;
+use tower::Layer;
 use tower_http::services::ServeDir;
 
-use crate::api::depth::init_depth_model;
-use crate::api::ws_depth::ws_depth_handler;
+use crate::api::circuit_breaker::CircuitBreaker;
+use crate::api::depth::{
+    DepthInferenceQueue, ModelHealth, SharedDepthModel, init_depth_model, init_standby_model,
+    spawn_model_watchdog,
+};
+use crate::api::model_integrity::verify_model_checksums;
+use crate::api::ws_depth::{WsDepthState, ws_depth_handler};
+use crate::api::{depth_admin, depth_estimate, depth_preview, health};
+use crate::api::{models, openapi, version};
+use crate::config::ServerConfig;
+use crate::metrics;
+use crate::server::cors;
+use crate::server::jwt_auth::JwtAuthLayer;
+use crate::server::proxy_trust::require_loopback;
+use crate::server::response_transform::ResponseTransformPipeline;
+use crate::server::routing_rules::{self, RoutingRules};
+use std::sync::Arc;
 
 /// Find the models directory
 fn find_models_dir() -> PathBuf {
@@ -35,7 +52,10 @@ fn find_models_dir() -> PathBuf {
 }
 
 /// Proxy requests to Next.js dev server
-async fn proxy_to_nextjs(mut req: Request) -> Response {
+pub(crate) async fn proxy_to_nextjs(
+    mut req: Request,
+    transforms: &ResponseTransformPipeline,
+) -> Response {
     let proxy_url =
         std::env::var("SERVER_PROXY_URL").unwrap_or_else(|_| "http://127.0.0.1:3031".to_string());
 
@@ -78,10 +98,15 @@ async fn proxy_to_nextjs(mut req: Request) -> Response {
         }
     }
 
+    // Transforms need the raw HTML, so ask upstream not to compress
+    if !transforms.is_empty() {
+        req.headers_mut().remove(hyper::header::ACCEPT_ENCODING);
+    }
+
     let client = Client::builder(TokioExecutor::new()).build_http();
 
     match client.request(req).await {
-        Ok(response) => response.into_response(),
+        Ok(response) => transforms.apply(response.into_response()).await,
         Err(e) => {
             tracing::error!("Proxy error: {}", e);
             (StatusCode::BAD_GATEWAY, "Frontend server not available").into_response()
@@ -89,21 +114,164 @@ async fn proxy_to_nextjs(mut req: Request) -> Response {
     }
 }
 
+/// Require a JWT on every route in `router` when configured
+fn with_jwt<S>(router: Router<S>, config: &ServerConfig) -> anyhow::Result<Router<S>>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    match &config.jwt {
+        Some(jwt) => Ok(router.route_layer(JwtAuthLayer::new(jwt)?)),
+        None => Ok(router),
+    }
+}
+
+/// Guard admin routes with JWT when configured, else allow loopback clients only
+fn with_admin_auth<S>(router: Router<S>, config: &ServerConfig) -> anyhow::Result<Router<S>>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    match &config.jwt {
+        Some(_) => with_jwt(router, config),
+        None => {
+            tracing::info!("[AUTH] No JWT configured, admin routes accept loopback clients only");
+            Ok(router.route_layer(axum::middleware::from_fn(require_loopback)))
+        }
+    }
+}
+
 /// Register all routes
-pub async fn register_routes() -> Router {
+pub async fn register_routes(config: &ServerConfig) -> anyhow::Result<Router> {
     // Initialize depth model at startup
-    let depth_model = init_depth_model().await;
+    let depth_model = if config.depth.enabled {
+        init_depth_model(config.depth.device).await
+    } else {
+        tracing::info!("[DEPTH] Disabled, depth routes will report the model as unavailable");
+        SharedDepthModel::default()
+    };
+    let standby_model = init_standby_model(
+        config.depth.enabled && config.depth.warm_standby,
+        config.depth.device,
+    );
+    // Single worker shared by /ws/depth and /api/depth/estimate
+    let breaker = Arc::new(CircuitBreaker::default());
+    let model_health = Arc::new(ModelHealth::default());
+    if config.depth.enabled {
+        spawn_model_watchdog(
+            depth_model.clone(),
+            model_health.clone(),
+            config.depth.device,
+        );
+    }
+    let queue = DepthInferenceQueue::spawn(
+        depth_model.clone(),
+        breaker.clone(),
+        model_health,
+        &config.depth,
+    );
 
     // Setup ONNX model serving
     let models_dir = find_models_dir();
     tracing::info!("[MODELS] Serving ONNX models from {:?}", models_dir);
+    // Refuse to serve corrupt models to clients
+    let dir = models_dir.clone();
+    tokio::task::spawn_blocking(move || verify_model_checksums(&dir)).await??;
+
+    // Rules evaluated before the Next.js proxy fallback
+    let routing_rules = Arc::new(RoutingRules::load(config.routing_rules_file.as_deref())?);
+    // HTML rewrites applied to proxied Next.js responses
+    let transforms = Arc::new(ResponseTransformPipeline::from_config(
+        &config.proxy_transforms,
+    ));
+
+    // CORS per route group, keyed by path in config.cors.overrides
+    let policies = &config.cors;
+
+    if let Some(jwt) = &config.jwt {
+        tracing::info!(
+            "[AUTH] JWT required on /ws/depth and /api/depth/{{estimate,preview}} ({:?})",
+            jwt.algorithm
+        );
+    }
+
+    // WebSocket depth inference route, behind JWT auth when configured
+    let ws_router = with_jwt(
+        Router::new().route("/ws/depth", get(ws_depth_handler)),
+        config,
+    )?;
+
+    let router = ws_router
+        // Outside auth so preflights need no token
+        .route_layer(cors::layer_for(policies, "/ws/depth")?)
+        .with_state(WsDepthState {
+            queue: queue.clone(),
+            compression_level: config.depth_compression_level,
+        })
+        // HTTP depth inference route, behind the same JWT auth as /ws/depth
+        .merge(
+            with_jwt(depth_estimate::routes(queue.clone()), config)?
+                .route_layer(cors::layer_for(policies, "/api/depth/estimate")?),
+        )
+        // Colourised PNG preview, behind the same JWT auth as /ws/depth
+        .merge(
+            with_jwt(depth_preview::routes(queue), config)?
+                .route_layer(cors::layer_for(policies, "/api/depth/preview")?),
+        )
+        // Liveness and readiness probes, each with its own CORS key
+        .merge(health::liveness_routes().route_layer(cors::layer_for(policies, "/healthz")?))
+        .merge(
+            health::readiness_routes(
+                config.depth.enabled,
+                depth_model.clone(),
+                breaker,
+                config.memory_limit_mb,
+            )
+            .route_layer(cors::layer_for(policies, "/readyz")?),
+        )
+        // Build and backend info
+        .merge(
+            version::routes(depth_model.clone())
+                .route_layer(cors::layer_for(policies, "/api/version")?),
+        );
+
+    // Hot-swap endpoint for the warm standby model, never unauthenticated
+    let router = match standby_model {
+        Some(standby) => router.merge(
+            with_admin_auth(depth_admin::routes(depth_model, standby), config)?
+                .route_layer(cors::layer_for(policies, "/admin/depth/swap")?),
+        ),
+        None => router,
+    };
+
+    // Tokio runtime metrics, sampled in the background
+    #[cfg(feature = "metrics-runtime")]
+    let router = {
+        metrics::runtime::spawn_sampler(&tokio::runtime::Handle::current());
+        router.merge(
+            metrics::runtime::routes().route_layer(cors::layer_for(policies, "/metrics/runtime")?),
+        )
+    };
 
-    Router::new()
-        // WebSocket depth inference route
-        .route("/ws/depth", get(ws_depth_handler))
-        .with_state(depth_model)
+    let fallback_cors = cors::layer_for(policies, "/")?;
+    Ok(router
+        // Prometheus scrape endpoint
+        .merge(metrics::routes().route_layer(cors::layer_for(policies, "/metrics")?))
+        // OpenAPI spec and Swagger UI
+        .merge(openapi::routes().route_layer(cors::layer_for(policies, "/api/openapi.json")?))
+        // Streaming model downloads with Range support
+        .merge(
+            models::routes(models_dir.clone())
+                .route_layer(cors::layer_for(policies, "/api/models")?),
+        )
         // Serve ONNX models for client-side inference
-        .nest_service("/models", ServeDir::new(&models_dir))
-        // Fallback to Next.js proxy
-        .fallback(proxy_to_nextjs)
+        .nest_service(
+            "/models",
+            cors::layer_for(policies, "/models")?.layer(ServeDir::new(&models_dir)),
+        )
+        // Fallback through routing rules, then Next.js proxy
+        .fallback(
+            (move |req: Request| {
+                routing_rules::dispatch(routing_rules.clone(), transforms.clone(), req)
+            })
+            .layer(fallback_cors),
+        ))
 }
@@ -0,0 +1,291 @@
+use axum::{
+    extract::Request,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tower::ServiceExt;
+use tower_http::services::{ServeDir, ServeFile};
+use tracing::{debug, info};
+
+use super::response_transform::SharedTransformPipeline;
+
+/// In-process handler for a routing rule
+pub type BoxedHandler = Arc<dyn Fn(Request) -> BoxFuture<'static, Response> + Send + Sync>;
+
+/// What to do with a request matched by a rule
+#[derive(Clone)]
+pub enum RouteHandler {
+    /// Forward to the Next.js server
+    Proxy,
+    /// Serve a file, or a directory keyed by request path
+    Static(PathBuf),
+    /// Handle in-process (registered programmatically, not from file)
+    Handler(BoxedHandler),
+}
+
+/// Fallback routing rule, first match wins
+#[derive(Clone)]
+pub struct RoutingRule {
+    pub path_pattern: glob::Pattern,
+    /// Optional Content-Type prefix the request must carry
+    pub content_type: Option<String>,
+    pub handler: RouteHandler,
+}
+
+impl RoutingRule {
+    fn matches(&self, req: &Request) -> bool {
+        let content_type_matches = match &self.content_type {
+            Some(expected) => req
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|actual| actual.starts_with(expected.as_str())),
+            None => true,
+        };
+        content_type_matches && self.path_pattern.matches(req.uri().path())
+    }
+}
+
+/// Handler as written in the routing rules file
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RouteHandlerConfig {
+    Proxy,
+    Static(PathBuf),
+}
+
+/// Rule as written in the routing rules file
+#[derive(Debug, Deserialize)]
+struct RoutingRuleConfig {
+    path: String,
+    content_type: Option<String>,
+    handler: RouteHandlerConfig,
+}
+
+/// Ordered list of fallback rules
+#[derive(Clone, Default)]
+pub struct RoutingRules {
+    rules: Vec<RoutingRule>,
+}
+
+pub type SharedRoutingRules = Arc<RoutingRules>;
+
+impl RoutingRules {
+    /// Load the configured `routing_rules_file`, empty if unset
+    pub fn load(path: Option<&Path>) -> anyhow::Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self::default());
+        };
+        let rules = Self::from_file(path)?;
+        info!(
+            "[ROUTING] Loaded {} rules from {:?}",
+            rules.rules.len(),
+            path
+        );
+        Ok(rules)
+    }
+
+    /// Load rules from a JSON file, relative static paths resolve against the file
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read routing rules {:?}: {}", path, e))?;
+        let configs: Vec<RoutingRuleConfig> = serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid routing rules {:?}: {}", path, e))?;
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+
+        let rules = configs
+            .into_iter()
+            .map(|config| {
+                let RoutingRuleConfig {
+                    path: pattern,
+                    content_type,
+                    handler,
+                } = config;
+                let path_pattern = glob::Pattern::new(&pattern)
+                    .map_err(|e| anyhow::anyhow!("Invalid path pattern '{}': {}", pattern, e))?;
+                let handler = match handler {
+                    RouteHandlerConfig::Proxy => RouteHandler::Proxy,
+                    RouteHandlerConfig::Static(target) => {
+                        RouteHandler::Static(base_dir.join(target))
+                    }
+                };
+                Ok(RoutingRule {
+                    path_pattern,
+                    content_type,
+                    handler,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Append a rule (evaluated after existing rules)
+    pub fn push(&mut self, rule: RoutingRule) {
+        self.rules.push(rule);
+    }
+
+    fn find(&self, req: &Request) -> Option<&RouteHandler> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(req))
+            .map(|rule| &rule.handler)
+    }
+}
+
+/// Dispatch an unmatched request through the rules, defaulting to the proxy
+pub async fn dispatch(
+    rules: SharedRoutingRules,
+    transforms: SharedTransformPipeline,
+    req: Request,
+) -> Response {
+    match rules.find(&req) {
+        Some(RouteHandler::Static(path)) => {
+            debug!("[ROUTING] {} -> static {:?}", req.uri().path(), path);
+            let result = if path.is_dir() {
+                ServeDir::new(path).oneshot(req).await
+            } else {
+                ServeFile::new(path).oneshot(req).await
+            };
+            match result {
+                Ok(response) => response.into_response(),
+                Err(infallible) => match infallible {},
+            }
+        }
+        Some(RouteHandler::Handler(handler)) => {
+            debug!("[ROUTING] {} -> handler", req.uri().path());
+            handler(req).await
+        }
+        Some(RouteHandler::Proxy) | None => {
+            super::route_builder::proxy_to_nextjs(req, &transforms).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn rule(pattern: &str, content_type: Option<&str>, handler: RouteHandler) -> RoutingRule {
+        RoutingRule {
+            path_pattern: glob::Pattern::new(pattern).unwrap(),
+            content_type: content_type.map(str::to_string),
+            handler,
+        }
+    }
+
+    fn request(path: &str, content_type: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri(path);
+        if let Some(content_type) = content_type {
+            builder = builder.header(header::CONTENT_TYPE, content_type);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    /// Readable name of the handler a request resolves to
+    fn target(rules: &RoutingRules, req: &Request) -> Option<String> {
+        rules.find(req).map(|handler| match handler {
+            RouteHandler::Proxy => "proxy".to_string(),
+            RouteHandler::Static(path) => path.display().to_string(),
+            RouteHandler::Handler(_) => "handler".to_string(),
+        })
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let mut rules = RoutingRules::default();
+        rules.push(rule("/assets/special.js", None, RouteHandler::Proxy));
+        rules.push(rule("/assets/*", None, RouteHandler::Static("dist".into())));
+        rules.push(rule(
+            "/assets/*",
+            None,
+            RouteHandler::Static("other".into()),
+        ));
+
+        let special = request("/assets/special.js", None);
+        assert_eq!(target(&rules, &special).as_deref(), Some("proxy"));
+        let app = request("/assets/app.js", None);
+        assert_eq!(target(&rules, &app).as_deref(), Some("dist"));
+    }
+
+    #[test]
+    fn content_type_narrows_a_rule() {
+        let mut rules = RoutingRules::default();
+        rules.push(rule(
+            "/upload",
+            Some("application/json"),
+            RouteHandler::Static("json".into()),
+        ));
+        rules.push(rule("/upload", None, RouteHandler::Proxy));
+
+        let json = request("/upload", Some("application/json; charset=utf-8"));
+        assert_eq!(target(&rules, &json).as_deref(), Some("json"));
+        let form = request("/upload", Some("multipart/form-data"));
+        assert_eq!(target(&rules, &form).as_deref(), Some("proxy"));
+        let bare = request("/upload", None);
+        assert_eq!(target(&rules, &bare).as_deref(), Some("proxy"));
+    }
+
+    #[test]
+    fn unmatched_requests_fall_through() {
+        let mut rules = RoutingRules::default();
+        rules.push(rule("/docs/*", None, RouteHandler::Static("docs".into())));
+        assert_eq!(target(&rules, &request("/blog/post", None)), None);
+        assert_eq!(target(&RoutingRules::default(), &request("/", None)), None);
+    }
+
+    #[tokio::test]
+    async fn dispatch_runs_in_process_handlers() {
+        let mut rules = RoutingRules::default();
+        let handler: BoxedHandler =
+            Arc::new(|_req| Box::pin(async { "from handler".into_response() }));
+        rules.push(rule("/hook", None, RouteHandler::Handler(handler)));
+
+        let response = dispatch(Arc::new(rules), Arc::default(), request("/hook", None)).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"from handler");
+    }
+
+    #[test]
+    fn file_rules_resolve_static_paths_against_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rules.json");
+        std::fs::write(
+            &path,
+            r#"[
+                {"path": "/api/*", "handler": "proxy"},
+                {"path": "/*", "content_type": "text/", "handler": {"static": "public"}}
+            ]"#,
+        )
+        .unwrap();
+        let rules = RoutingRules::load(Some(&path)).unwrap();
+
+        let api = request("/api/users", Some("text/plain"));
+        assert_eq!(target(&rules, &api).as_deref(), Some("proxy"));
+        let page = request("/index.html", Some("text/html"));
+        let public = dir.path().join("public").display().to_string();
+        assert_eq!(target(&rules, &page), Some(public));
+    }
+
+    #[test]
+    fn invalid_rules_files_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rules.json");
+        std::fs::write(&path, r#"[{"path": "/[", "handler": "proxy"}]"#).unwrap();
+        let error = match RoutingRules::from_file(&path) {
+            Ok(_) => panic!("invalid glob accepted"),
+            Err(e) => e.to_string(),
+        };
+        assert!(error.starts_with("Invalid path pattern '/['"), "{}", error);
+
+        std::fs::write(&path, r#"[{"path": "/", "handler": "redirect"}]"#).unwrap();
+        assert!(RoutingRules::from_file(&path).is_err());
+        assert!(RoutingRules::from_file(&dir.path().join("missing.json")).is_err());
+    }
+}
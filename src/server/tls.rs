@@ -0,0 +1,286 @@
+use axum::Router;
+use axum::extract::ConnectInfo;
+use hyper::body::Incoming;
+use hyper::service::service_fn;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::GracefulShutdown;
+use rustls::RootCertStore;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use std::fs::File;
+use std::future::Future;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tower::ServiceExt;
+use tracing::{debug, error, info};
+
+use super::client_cert::PeerCertificate;
+use crate::config::TlsConfig;
+
+fn load_certs(path: &Path) -> anyhow::Result<Vec<CertificateDer<'static>>> {
+    let file = File::open(path)
+        .map_err(|e| anyhow::anyhow!("Failed to open certificate {:?}: {}", path, e))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| anyhow::anyhow!("Invalid certificate PEM {:?}: {}", path, e))?;
+    anyhow::ensure!(!certs.is_empty(), "No certificates in {:?}", path);
+    Ok(certs)
+}
+
+fn load_key(path: &Path) -> anyhow::Result<PrivateKeyDer<'static>> {
+    let file =
+        File::open(path).map_err(|e| anyhow::anyhow!("Failed to open key {:?}: {}", path, e))?;
+    rustls_pemfile::private_key(&mut BufReader::new(file))
+        .map_err(|e| anyhow::anyhow!("Invalid key PEM {:?}: {}", path, e))?
+        .ok_or_else(|| anyhow::anyhow!("No private key in {:?}", path))
+}
+
+/// Build an acceptor from the configured PEM files
+pub fn acceptor(config: &TlsConfig) -> anyhow::Result<TlsAcceptor> {
+    let TlsConfig {
+        cert_path,
+        key_path,
+        client_ca,
+        require_client_cert,
+    } = config;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()?;
+    let builder = match client_ca {
+        Some(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(ca_path)? {
+                roots.add(cert)?;
+            }
+            // Anonymous clients still complete the handshake; ClientCertLayer answers them with 401
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .allow_unauthenticated()
+                .build()?;
+            info!(
+                "[TLS] Client certificates verified against {:?}, required: {}",
+                ca_path, require_client_cert
+            );
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let mut server_config =
+        builder.with_single_cert(load_certs(cert_path)?, load_key(key_path)?)?;
+    // Same protocols axum negotiates in the clear
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    info!("[TLS] Loaded certificate {:?}", cert_path);
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/// Serve `app` over TLS until `shutdown` resolves
+pub async fn serve(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    app: Router,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()> {
+    let builder = auto::Builder::new(TokioExecutor::new());
+    let graceful = GracefulShutdown::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    error!("[TLS] Accept error: {}", e);
+                    continue;
+                }
+            },
+            () = &mut shutdown => break,
+        };
+
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        let builder = builder.clone();
+        let watcher = graceful.watcher();
+        // Handshake off the accept loop so a slow client cannot stall others
+        tokio::spawn(async move {
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    debug!("[TLS] Handshake with {} failed: {}", peer, e);
+                    return;
+                }
+            };
+            let (_, session) = stream.get_ref();
+            debug!(
+                "[TLS] {} connected with {:?} {:?}",
+                peer,
+                session.protocol_version(),
+                session.negotiated_cipher_suite().map(|suite| suite.suite())
+            );
+
+            let peer_cert = session
+                .peer_certificates()
+                .and_then(|certs| certs.first())
+                .map(|cert| PeerCertificate(cert.clone().into_owned()));
+
+            // ProxyTrustLayer and the access log read the peer from ConnectInfo
+            let service = service_fn(move |mut req: hyper::Request<Incoming>| {
+                req.extensions_mut().insert(ConnectInfo(peer));
+                if let Some(peer_cert) = &peer_cert {
+                    req.extensions_mut().insert(peer_cert.clone());
+                }
+                app.clone().oneshot(req)
+            });
+            let connection = builder.serve_connection_with_upgrades(TokioIo::new(stream), service);
+            if let Err(e) = watcher.watch(connection.into_owned()).await {
+                debug!("[TLS] Connection from {} ended: {}", peer, e);
+            }
+        });
+    }
+
+    drop(listener);
+    graceful.shutdown().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::client_cert::{ClientCertLayer, ClientSubject};
+    use axum::{Extension, routing::get};
+    use rcgen::{
+        BasicConstraints, Certificate, CertificateParams, CertifiedIssuer, DnType,
+        ExtendedKeyUsagePurpose, IsCa, KeyPair,
+    };
+    use rustls::pki_types::ServerName;
+    use std::net::SocketAddr;
+    use tempfile::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+    use tokio_rustls::TlsConnector;
+
+    fn ca(name: &str) -> CertifiedIssuer<'static, KeyPair> {
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.distinguished_name.push(DnType::CommonName, name);
+        CertifiedIssuer::self_signed(params, KeyPair::generate().unwrap()).unwrap()
+    }
+
+    fn leaf(
+        ca: &CertifiedIssuer<'_, KeyPair>,
+        name: &str,
+        usage: ExtendedKeyUsagePurpose,
+    ) -> (Certificate, KeyPair) {
+        let mut params = CertificateParams::new(vec![name.to_string()]).unwrap();
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.extended_key_usages = vec![usage];
+        let key = KeyPair::generate().unwrap();
+        (params.signed_by(&key, ca).unwrap(), key)
+    }
+
+    /// TLS server requiring client certificates from `ca`, echoing the client subject
+    async fn start(ca: &CertifiedIssuer<'_, KeyPair>) -> (SocketAddr, TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let (cert, key) = leaf(ca, "localhost", ExtendedKeyUsagePurpose::ServerAuth);
+        let config = TlsConfig {
+            cert_path: dir.path().join("cert.pem"),
+            key_path: dir.path().join("key.pem"),
+            client_ca: Some(dir.path().join("ca.pem")),
+            require_client_cert: true,
+        };
+        std::fs::write(&config.cert_path, cert.pem()).unwrap();
+        std::fs::write(&config.key_path, key.serialize_pem()).unwrap();
+        std::fs::write(dir.path().join("ca.pem"), ca.pem()).unwrap();
+
+        let app =
+            Router::new()
+                .route(
+                    "/",
+                    get(
+                        |Extension(ClientSubject(subject)): Extension<ClientSubject>| async move {
+                            subject
+                        },
+                    ),
+                )
+                .route_layer(ClientCertLayer);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(
+            listener,
+            acceptor(&config).unwrap(),
+            app,
+            std::future::pending(),
+        ));
+        (addr, dir)
+    }
+
+    /// GET / over TLS trusting `ca`, presenting `client` if given
+    async fn get_root(
+        addr: SocketAddr,
+        ca: &CertifiedIssuer<'_, KeyPair>,
+        client: Option<(Certificate, KeyPair)>,
+    ) -> anyhow::Result<String> {
+        let mut roots = RootCertStore::empty();
+        roots.add(ca.der().clone())?;
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = rustls::ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots);
+        let config = match client {
+            Some((cert, key)) => builder.with_client_auth_cert(
+                vec![cert.der().clone()],
+                PrivateKeyDer::try_from(key.serialize_der()).map_err(anyhow::Error::msg)?,
+            )?,
+            None => builder.with_no_client_auth(),
+        };
+
+        let stream = TcpStream::connect(addr).await?;
+        let mut tls = TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost")?, stream)
+            .await?;
+        tls.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await?;
+        let mut response = String::new();
+        tls.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    #[tokio::test]
+    async fn trusted_client_cert_is_accepted() {
+        let ca = ca("Test CA");
+        let (addr, _dir) = start(&ca).await;
+        let client = leaf(&ca, "depth-client", ExtendedKeyUsagePurpose::ClientAuth);
+
+        let response = get_root(addr, &ca, Some(client)).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("CN=depth-client"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn untrusted_client_cert_is_rejected() {
+        let ca = ca("Test CA");
+        let (addr, _dir) = start(&ca).await;
+        let rogue = leaf(
+            &self::ca("Rogue CA"),
+            "depth-client",
+            ExtendedKeyUsagePurpose::ClientAuth,
+        );
+
+        // Handshake fails, so no HTTP response comes back
+        let response = get_root(addr, &ca, Some(rogue)).await;
+        assert!(response.is_err(), "{:?}", response);
+    }
+
+    #[tokio::test]
+    async fn missing_client_cert_gets_401() {
+        let ca = ca("Test CA");
+        let (addr, _dir) = start(&ca).await;
+
+        let response = get_root(addr, &ca, None).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 401"), "{}", response);
+    }
+}
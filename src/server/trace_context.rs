@@ -0,0 +1,20 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+use opentelemetry::global;
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use super::jwt_auth::redacted_uri;
+use crate::telemetry::HeaderExtractor;
+
+/// Root span per request, parented to an incoming `traceparent` when present
+pub async fn propagate_trace_context(req: Request, next: Next) -> Response {
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(req.headers()))
+    });
+    let span =
+        tracing::info_span!("http_request", method = %req.method(), uri = %redacted_uri(req.uri()));
+    if let Err(e) = span.set_parent(parent) {
+        tracing::debug!("[OTEL] Could not attach trace context: {}", e);
+    }
+    next.run(req).instrument(span).await
+}
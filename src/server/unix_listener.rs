@@ -0,0 +1,127 @@
+use axum::Router;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::GracefulShutdown;
+use hyper_util::service::TowerToHyperService;
+use std::future::Future;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use tokio::net::UnixListener;
+use tracing::{error, info, warn};
+
+/// Bind `path`, replacing a stale socket file left by an unclean exit
+pub fn bind(path: &Path) -> anyhow::Result<UnixListener> {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_socket() => {
+            warn!("[UNIX] Removing stale socket {:?}", path);
+            std::fs::remove_file(path)?;
+        }
+        Ok(_) => anyhow::bail!("{:?} exists and is not a socket", path),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    UnixListener::bind(path)
+        .map_err(|e| anyhow::anyhow!("Failed to bind unix socket {:?}: {}", path, e))
+}
+
+/// Serve `app` on a Unix domain socket until `shutdown` resolves, then remove the socket file
+pub async fn serve(
+    listener: UnixListener,
+    path: PathBuf,
+    app: Router,
+    shutdown: impl Future<Output = ()>,
+) {
+    let builder = auto::Builder::new(TokioExecutor::new());
+    let graceful = GracefulShutdown::new();
+    tokio::pin!(shutdown);
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    error!("[UNIX] Accept error: {}", e);
+                    continue;
+                }
+            },
+            () = &mut shutdown => break,
+        };
+
+        let service = TowerToHyperService::new(app.clone());
+        // Upgrades keep /ws/depth working over the socket
+        let connection = builder
+            .serve_connection_with_upgrades(TokioIo::new(stream), service)
+            .into_owned();
+        let connection = graceful.watch(connection);
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                error!("[UNIX] Connection error: {}", e);
+            }
+        });
+    }
+
+    drop(listener);
+    graceful.shutdown().await;
+    match std::fs::remove_file(&path) {
+        Ok(()) => info!("[UNIX] Removed socket {:?}", path),
+        Err(e) => error!("[UNIX] Failed to remove socket {:?}: {}", path, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixStream;
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn serves_requests_and_removes_socket_on_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("depth.sock");
+        let app = Router::new().route("/healthz", get(|| async { "ok" }));
+
+        let (stop, stopped) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve(bind(&path).unwrap(), path.clone(), app, async {
+            let _ = stopped.await;
+        }));
+
+        let mut stream = UnixStream::connect(&path).await.unwrap();
+        stream
+            .write_all(b"GET /healthz HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("ok"), "{}", response);
+
+        stop.send(()).unwrap();
+        server.await.unwrap();
+        assert!(!path.exists(), "socket file left behind");
+    }
+
+    #[tokio::test]
+    async fn replaces_stale_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("depth.sock");
+        // Dropping a listener leaves its socket file behind, as after a crash
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let listener = bind(&path).unwrap();
+        assert!(UnixStream::connect(&path).await.is_ok());
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn refuses_to_replace_regular_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("depth.sock");
+        std::fs::write(&path, "not a socket").unwrap();
+
+        assert!(bind(&path).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a socket");
+    }
+}
@@ -0,0 +1,95 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::{global, propagation::Extractor};
+use opentelemetry_otlp::SpanExporter;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing::Subscriber;
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry};
+
+use crate::config::LogFormat;
+
+/// Enables OTLP export when set, e.g. http://localhost:4318
+pub const OTLP_ENDPOINT_VAR: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Console-only subscriber for startup, before the process may fork
+pub fn console_subscriber(
+    filter: &str,
+    format: LogFormat,
+) -> anyhow::Result<impl Subscriber + Send + Sync> {
+    Ok(Registry::default()
+        .with(console_layer(format))
+        .with(EnvFilter::try_new(filter)?))
+}
+
+/// JSON lines carry an RFC 3339 timestamp, level, target, message and the current span's fields
+fn console_layer<S>(format: LogFormat) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+    }
+}
+
+/// Install the global subscriber, exporting spans over OTLP when the endpoint is set.
+/// Spawns the batch exporter thread, so call after any fork.
+pub fn init(filter: &str, format: LogFormat) -> anyhow::Result<Option<SdkTracerProvider>> {
+    let provider = match std::env::var(OTLP_ENDPOINT_VAR) {
+        Ok(_) => Some(tracer_provider()?),
+        Err(_) => None,
+    };
+    let otel = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(env!("CARGO_PKG_NAME")))
+    });
+    Registry::default()
+        .with(otel)
+        .with(console_layer(format))
+        .with(EnvFilter::try_new(filter)?)
+        .try_init()?;
+
+    if let Some(provider) = &provider {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        global::set_tracer_provider(provider.clone());
+        if let Ok(endpoint) = std::env::var(OTLP_ENDPOINT_VAR) {
+            tracing::info!("[OTEL] Exporting traces to {}", endpoint);
+        }
+    }
+    Ok(provider)
+}
+
+fn tracer_provider() -> anyhow::Result<SdkTracerProvider> {
+    // Exporter reads the endpoint (and appends /v1/traces) from the env itself
+    let exporter = SpanExporter::builder().with_http().build()?;
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            Resource::builder()
+                .with_service_name(env!("CARGO_PKG_NAME"))
+                .build(),
+        )
+        .build())
+}
+
+/// W3C trace context read from HTTP headers
+pub struct HeaderExtractor<'a>(pub &'a axum::http::HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
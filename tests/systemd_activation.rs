@@ -0,0 +1,99 @@
+//! Socket activation against the real binary: systemd hands over a bound
+//! listener as fd 3 (LISTEN_FDS) and waits for READY=1 on NOTIFY_SOCKET
+#![cfg(unix)]
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::fd::{AsRawFd, BorrowedFd, IntoRawFd};
+use std::os::unix::net::UnixDatagram;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use nix::fcntl::{FcntlArg, FdFlag, fcntl};
+use nix::unistd::dup2_raw;
+
+/// First fd systemd passes (SD_LISTEN_FDS_START)
+const LISTEN_FDS_START: i32 = 3;
+
+/// Kill the server even when an assertion fails
+struct ServerGuard(Child);
+
+impl Drop for ServerGuard {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+#[test]
+fn serves_inherited_socket_and_notifies_ready() {
+    let dir = tempfile::tempdir().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let notify_path = dir.path().join("notify.sock");
+    let notify = UnixDatagram::bind(&notify_path).unwrap();
+    notify
+        .set_read_timeout(Some(Duration::from_secs(30)))
+        .unwrap();
+
+    let listener_fd = listener.as_raw_fd();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_server"));
+    command
+        .args(["--systemd-socket", "--no-depth"])
+        // Empty cwd: no .env.local, config.toml or models dir to pick up
+        .current_dir(dir.path())
+        .env("LISTEN_FDS", "1")
+        .env_remove("LISTEN_PID")
+        .env("NOTIFY_SOCKET", &notify_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Runs in the forked child before exec; only async-signal-safe calls
+    unsafe {
+        command.pre_exec(move || {
+            if listener_fd == LISTEN_FDS_START {
+                // dup2 onto itself keeps close-on-exec, clear it by hand
+                let fd = BorrowedFd::borrow_raw(listener_fd);
+                fcntl(fd, FcntlArg::F_SETFD(FdFlag::empty()))?;
+            } else {
+                let fd = BorrowedFd::borrow_raw(listener_fd);
+                // The new fd must outlive exec, so never close it here
+                let _ = dup2_raw(fd, LISTEN_FDS_START)?.into_raw_fd();
+            }
+            Ok(())
+        });
+    }
+    let _server = ServerGuard(command.spawn().unwrap());
+    // The child holds its own copy now
+    drop(listener);
+
+    let mut buf = [0u8; 256];
+    let len = notify
+        .recv(&mut buf)
+        .expect("server never sent a notification");
+    let message = String::from_utf8_lossy(&buf[..len]);
+    assert!(
+        message.lines().any(|line| line == "READY=1"),
+        "unexpected notification: {:?}",
+        message
+    );
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .unwrap();
+    write!(
+        stream,
+        "GET /healthz HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        addr
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(
+        response.starts_with("HTTP/1.1 200"),
+        "unexpected response: {:?}",
+        response.lines().next()
+    );
+}
@@ -0,0 +1,122 @@
+//! With RUST_LOG=depth_browser=trace every frame should leave a try_infer span
+//! and an estimate span, whatever depth format the client asked for
+
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+use depth_browser::api::circuit_breaker::CircuitBreaker;
+use depth_browser::api::depth::{DepthFormat, DepthInferenceQueue, DepthModel, ModelHealth};
+use depth_browser::config::{DepthConfig, DepthDevice};
+use image::{ImageFormat, RgbImage};
+use pyo3::prelude::*;
+use pyo3::types::PyModule;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+/// Stands in for depth_estimator_onnx: 2x2 maps, no numpy or model files
+const FAKE_ESTIMATOR: &std::ffi::CStr = cr#"
+class DepthEstimator:
+    def __init__(self, device):
+        self.device = device
+
+    def estimate(self, jpeg):
+        return (2).to_bytes(2, 'big') * 2 + bytes(4)
+
+    def estimate_u16(self, jpeg):
+        return (2).to_bytes(2, 'big') * 2 + bytes(8)
+"#;
+
+/// Records "name" or "name(method)" for every span opened
+#[derive(Clone, Default)]
+struct SpanNames(Arc<Mutex<Vec<String>>>);
+
+struct MethodField(Option<String>);
+
+impl Visit for MethodField {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "method" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "method" {
+            self.0 = Some(format!("{:?}", value).trim_matches('"').to_string());
+        }
+    }
+}
+
+impl<S: tracing::Subscriber> Layer<S> for SpanNames {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        let mut method = MethodField(None);
+        attrs.record(&mut method);
+        let name = attrs.metadata().name();
+        let entry = match method.0 {
+            Some(method) => format!("{}({})", name, method),
+            None => name.to_string(),
+        };
+        self.0.lock().unwrap().push(entry);
+    }
+}
+
+fn jpeg() -> Vec<u8> {
+    let mut out = Cursor::new(Vec::new());
+    RgbImage::new(8, 8)
+        .write_to(&mut out, ImageFormat::Jpeg)
+        .unwrap();
+    out.into_inner()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn every_format_emits_try_infer_and_estimate_spans() {
+    // Inference runs on blocking threads, so the subscriber has to be global
+    let spans = SpanNames::default();
+    let subscriber = Registry::default().with(
+        spans
+            .clone()
+            .with_filter(EnvFilter::new("depth_browser=trace")),
+    );
+    tracing::subscriber::set_global_default(subscriber).unwrap();
+
+    // Only this test runs in this binary, nothing reads the environment concurrently
+    unsafe { std::env::set_var("DEPTH_SKIP_AUTO_INSTALL", "1") };
+    Python::with_gil(|py| {
+        let module = PyModule::from_code(
+            py,
+            FAKE_ESTIMATOR,
+            c"depth_estimator_onnx.py",
+            c"depth_estimator_onnx",
+        )?;
+        py.import("sys")?
+            .getattr("modules")?
+            .set_item("depth_estimator_onnx", module)
+    })
+    .unwrap();
+    let model = DepthModel::new(DepthDevice::Cpu).unwrap();
+
+    let queue = DepthInferenceQueue::spawn(
+        Arc::new(tokio::sync::Mutex::new(Some(model))),
+        Arc::new(CircuitBreaker::default()),
+        Arc::new(ModelHealth::default()),
+        &DepthConfig::default(),
+    );
+    for format in [DepthFormat::U8, DepthFormat::U16] {
+        queue.try_infer(format, jpeg(), None).await.unwrap();
+    }
+
+    let spans = spans.0.lock().unwrap().clone();
+    let expected = [
+        "try_infer",
+        "estimate(estimate)",
+        "try_infer",
+        "estimate(estimate_u16)",
+    ];
+    let seen: Vec<&str> = spans
+        .iter()
+        .map(String::as_str)
+        .filter(|name| expected.contains(name))
+        .collect();
+    assert_eq!(seen, expected, "all spans: {:?}", spans);
+}
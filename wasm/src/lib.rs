@@ -1,3 +1,5 @@
+pub mod shared_worker;
+
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(start)]
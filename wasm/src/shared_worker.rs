@@ -0,0 +1,178 @@
+use js_sys::{Object, Reflect, Uint8Array};
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use web_sys::{BinaryType, BroadcastChannel, MessageEvent, WebSocket};
+
+/// Tab bookkeeping shared between the socket and channel callbacks
+#[derive(Default)]
+struct MuxState {
+    tabs: HashSet<u32>,
+    /// Tab ids of frames in flight; the server answers in order
+    in_flight: VecDeque<u32>,
+}
+
+/// Message a tab posts on the BroadcastChannel
+enum TabMessage {
+    Register(u32),
+    Unregister(u32),
+    Frame { tab_id: u32, jpeg: Vec<u8> },
+}
+
+impl TabMessage {
+    fn parse(data: &JsValue) -> Option<Self> {
+        let kind = Reflect::get(data, &"type".into()).ok()?.as_string()?;
+        let tab_id = Reflect::get(data, &"tab_id".into()).ok()?.as_f64()? as u32;
+        match kind.as_str() {
+            "register" => Some(Self::Register(tab_id)),
+            "unregister" => Some(Self::Unregister(tab_id)),
+            "frame" => {
+                let jpeg = Reflect::get(data, &"jpeg".into()).ok()?;
+                let jpeg = jpeg.dyn_into::<Uint8Array>().ok()?.to_vec();
+                Some(Self::Frame { tab_id, jpeg })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Shares one /ws/depth connection between tabs via a BroadcastChannel
+///
+/// Tabs post `{type: "register" | "unregister", tab_id}` and
+/// `{type: "frame", tab_id, jpeg: Uint8Array}`; responses come back as
+/// `{type: "depth", tab_id, depth: Uint8Array}` or `{type: "error", tab_id, message}`.
+#[wasm_bindgen]
+pub struct DepthSharedWorker {
+    socket: WebSocket,
+    channel: BroadcastChannel,
+    state: Rc<RefCell<MuxState>>,
+    _on_socket_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_channel_message: Closure<dyn FnMut(MessageEvent)>,
+}
+
+fn reply(channel: &BroadcastChannel, kind: &str, tab_id: u32, key: &str, value: &JsValue) {
+    let message = Object::new();
+    let fields = [
+        ("type", JsValue::from_str(kind)),
+        ("tab_id", JsValue::from(tab_id)),
+        (key, value.clone()),
+    ];
+    for (name, field) in fields {
+        Reflect::set(&message, &name.into(), &field).expect("set on plain object");
+    }
+    if let Err(e) = channel.post_message(&message) {
+        log::error!("[DEPTH-WORKER] Failed to post to channel: {:?}", e);
+    }
+}
+
+fn send_frame(
+    socket: &WebSocket,
+    state: &RefCell<MuxState>,
+    tab_id: u32,
+    jpeg: &[u8],
+) -> Result<(), JsValue> {
+    let mut state = state.borrow_mut();
+    if !state.tabs.contains(&tab_id) {
+        return Err(JsValue::from_str(&format!(
+            "tab {} is not registered",
+            tab_id
+        )));
+    }
+    socket.send_with_u8_array(jpeg)?;
+    state.in_flight.push_back(tab_id);
+    Ok(())
+}
+
+#[wasm_bindgen]
+impl DepthSharedWorker {
+    /// Open the depth socket at `url` and listen on `channel_name`
+    #[wasm_bindgen(constructor)]
+    pub fn new(url: &str, channel_name: &str) -> Result<DepthSharedWorker, JsValue> {
+        let socket = WebSocket::new(url)?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+        let channel = BroadcastChannel::new(channel_name)?;
+        let state = Rc::new(RefCell::new(MuxState::default()));
+
+        let on_socket_message = {
+            let channel = channel.clone();
+            let state = state.clone();
+            Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                let data = event.data();
+                // Server pong replies are not tied to a frame
+                if data.as_string().as_deref() == Some("pong") {
+                    return;
+                }
+                let Some(tab_id) = state.borrow_mut().in_flight.pop_front() else {
+                    log::warn!("[DEPTH-WORKER] Response with no frame in flight");
+                    return;
+                };
+                if !state.borrow().tabs.contains(&tab_id) {
+                    return;
+                }
+                match data.as_string() {
+                    Some(text) => reply(&channel, "error", tab_id, "message", &text.into()),
+                    None => {
+                        let depth = Uint8Array::new(&data);
+                        reply(&channel, "depth", tab_id, "depth", &depth.into());
+                    }
+                }
+            })
+        };
+        socket.set_onmessage(Some(on_socket_message.as_ref().unchecked_ref()));
+
+        let on_channel_message = {
+            let socket = socket.clone();
+            let state = state.clone();
+            Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+                match TabMessage::parse(&event.data()) {
+                    Some(TabMessage::Register(tab_id)) => {
+                        state.borrow_mut().tabs.insert(tab_id);
+                    }
+                    Some(TabMessage::Unregister(tab_id)) => {
+                        state.borrow_mut().tabs.remove(&tab_id);
+                    }
+                    Some(TabMessage::Frame { tab_id, jpeg }) => {
+                        if let Err(e) = send_frame(&socket, &state, tab_id, &jpeg) {
+                            log::warn!("[DEPTH-WORKER] Dropped frame: {:?}", e);
+                        }
+                    }
+                    None => log::warn!("[DEPTH-WORKER] Ignoring malformed channel message"),
+                }
+            })
+        };
+        channel.set_onmessage(Some(on_channel_message.as_ref().unchecked_ref()));
+
+        log::info!("[DEPTH-WORKER] Sharing {} on channel {}", url, channel_name);
+
+        Ok(DepthSharedWorker {
+            socket,
+            channel,
+            state,
+            _on_socket_message: on_socket_message,
+            _on_channel_message: on_channel_message,
+        })
+    }
+
+    /// Register a tab so its frames are accepted and responses delivered
+    pub fn register_tab(&self, tab_id: u32) {
+        self.state.borrow_mut().tabs.insert(tab_id);
+    }
+
+    /// Stop delivering responses to a tab
+    pub fn unregister_tab(&self, tab_id: u32) {
+        self.state.borrow_mut().tabs.remove(&tab_id);
+    }
+
+    /// Send a JPEG frame on behalf of `tab_id`
+    pub fn post_frame(&self, tab_id: u32, jpeg: &[u8]) -> Result<(), JsValue> {
+        send_frame(&self.socket, &self.state, tab_id, jpeg)
+    }
+
+    /// Close the socket and the channel
+    pub fn close(&self) -> Result<(), JsValue> {
+        self.channel.close();
+        self.socket.close()
+    }
+}
@@ -0,0 +1,129 @@
+use anyhow::{Context, ensure};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const USAGE: &str = "Usage: cargo xtask bundle [--tar]
+
+Commands:
+  bundle    Build the release server and assemble dist/ with python/ and models/
+            --tar  Also write dist.tar.gz";
+
+const START_SCRIPT: &str = r#"#!/usr/bin/env bash
+set -euo pipefail
+DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+cd "$DIR"
+export DEPTH_SKIP_AUTO_INSTALL=1
+export PYTHONPATH="$DIR/python${PYTHONPATH:+:$PYTHONPATH}"
+exec "$DIR/server" "$@"
+"#;
+
+/// xtask subcommands
+enum Task {
+    Bundle { tar: bool },
+}
+
+impl Task {
+    fn parse(args: &[String]) -> anyhow::Result<Self> {
+        match args {
+            [cmd] if cmd == "bundle" => Ok(Self::Bundle { tar: false }),
+            [cmd, flag] if cmd == "bundle" && flag == "--tar" => Ok(Self::Bundle { tar: true }),
+            _ => anyhow::bail!("{}", USAGE),
+        }
+    }
+}
+
+fn project_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives in the workspace root")
+        .to_path_buf()
+}
+
+fn run(cmd: &mut Command) -> anyhow::Result<()> {
+    let status = cmd
+        .status()
+        .with_context(|| format!("failed to spawn {:?}", cmd))?;
+    ensure!(status.success(), "{:?} exited with {}", cmd, status);
+    Ok(())
+}
+
+fn copy_dir(src: &Path, dest: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(src).with_context(|| format!("reading {:?}", src))? {
+        let entry = entry?;
+        let path = entry.path();
+        let target = dest.join(entry.file_name());
+        // Skip caches and local virtualenvs
+        let name = entry.file_name();
+        if matches!(
+            name.to_str(),
+            Some("__pycache__" | ".venv" | "venv" | ".pytest_cache")
+        ) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            fs::copy(&path, &target).with_context(|| format!("copying {:?}", path))?;
+        }
+    }
+    Ok(())
+}
+
+fn bundle(tar: bool) -> anyhow::Result<()> {
+    let root = project_root();
+    let dist = root.join("dist");
+
+    println!("Building release server...");
+    run(Command::new(env!("CARGO")).current_dir(&root).args([
+        "build",
+        "--release",
+        "--bin",
+        "server",
+    ]))?;
+
+    if dist.exists() {
+        fs::remove_dir_all(&dist)?;
+    }
+    fs::create_dir_all(&dist)?;
+
+    let binary = format!("server{}", std::env::consts::EXE_SUFFIX);
+    fs::copy(
+        root.join("target").join("release").join(&binary),
+        dist.join(&binary),
+    )?;
+    copy_dir(&root.join("python"), &dist.join("python"))?;
+
+    let models = root.join("models");
+    if models.exists() {
+        copy_dir(&models, &dist.join("models"))?;
+    } else {
+        println!("WARNING: models/ not found, run `just src::download-models` first");
+    }
+
+    let start = dist.join("start.sh");
+    fs::write(&start, START_SCRIPT)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&start, fs::Permissions::from_mode(0o755))?;
+    }
+
+    if tar {
+        println!("Packaging dist.tar.gz...");
+        run(Command::new("tar")
+            .current_dir(&root)
+            .args(["-czf", "dist.tar.gz", "dist"]))?;
+    }
+
+    println!("Bundle ready: {}", dist.display());
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match Task::parse(&args)? {
+        Task::Bundle { tar } => bundle(tar),
+    }
+}